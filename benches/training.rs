@@ -0,0 +1,21 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use menace::*;
+
+/// The number of training games played before measuring, so most positions have been seen.
+const WARM_UP_GAMES: u32 = 10000;
+
+fn bench_training<G: Game>(c: &mut Criterion, name: &str) {
+    let mut machine = Machine::<G>::new();
+    for _ in 0..WARM_UP_GAMES {
+        machine.play_training_match();
+    }
+    c.bench_function(name, |b| b.iter(|| machine.play_training_match()));
+}
+
+fn training(c: &mut Criterion) {
+    bench_training::<State>(c, "training tic-tac-toe");
+    bench_training::<UltimateState>(c, "training ultimate tic-tac-toe");
+}
+
+criterion_group!(benches, training);
+criterion_main!(benches);
@@ -0,0 +1,99 @@
+use crate::*;
+
+/// A player which selects moves in a `Game`, like the machine or an external engine.
+pub trait Agent<G: Game = State> {
+    /// Select a move for `turn` in a position where the game is not over. Returns `Ok(None)` to
+    /// resign, or the reason why the opponent wins if the agent forfeits the game.
+    fn select_move(&mut self, pos: G, turn: Player) -> Result<Option<G::Move>, WinReason>;
+
+    /// Called before the first move of a new game.
+    fn new_game(&mut self) {}
+
+    /// Called when a game is over.
+    fn game_over(&mut self, _result: GameResult) {}
+}
+
+impl<G: Game> Agent<G> for Machine<G> {
+    fn select_move(&mut self, pos: G, _turn: Player) -> Result<Option<G::Move>, WinReason> {
+        Ok(Machine::select_move(self, pos))
+    }
+}
+
+impl<G: Game> Agent<G> for Solver<G> {
+    /// Select the first of the best moves, so the solver never loses.
+    fn select_move(&mut self, pos: G, turn: Player) -> Result<Option<G::Move>, WinReason> {
+        Ok(self.best_moves(pos, turn).first().copied())
+    }
+}
+
+/// Play a game between two agents and return the result. An illegal move forfeits the game.
+pub fn play_match<'a, G: Game>(
+    crosses: &mut (dyn Agent<G> + 'a),
+    naughts: &mut (dyn Agent<G> + 'a),
+) -> GameResult {
+    crosses.new_game();
+    naughts.new_game();
+    let mut pos = G::new();
+    let mut turn = Crosses;
+    let result = loop {
+        let agent = match turn {
+            Crosses => &mut *crosses,
+            Naughts => &mut *naughts,
+        };
+        let forfeit = |reason| Win {
+            winner: turn.opponent(),
+            reason,
+        };
+        let m = match agent.select_move(pos, turn) {
+            Ok(Some(m)) if pos.legal_moves().any(|legal| legal == m) => m,
+            Ok(Some(_)) => break forfeit(IllegalMove),
+            Ok(None) => break forfeit(Resignation),
+            Err(reason) => break forfeit(reason),
+        };
+        pos.play(m, turn);
+        if let Some(result) = pos.result(turn) {
+            break result;
+        }
+        turn = turn.opponent();
+    };
+    crosses.game_over(result);
+    naughts.game_over(result);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An agent which always plays in the corner.
+    struct Corner;
+
+    impl Agent for Corner {
+        fn select_move(&mut self, _pos: State, _turn: Player) -> Result<Option<Move>, WinReason> {
+            Ok(Some(Move::from_usize(0)))
+        }
+    }
+
+    #[test]
+    fn test_play_match() {
+        assert_eq!(
+            play_match::<State>(&mut Solver::new(), &mut Solver::new()),
+            Draw { reason: BoardFull }
+        );
+        let result = play_match::<State>(&mut Machine::new(), &mut Solver::new());
+        assert!(!matches!(
+            result,
+            Win {
+                winner: Crosses,
+                ..
+            }
+        ));
+        assert_eq!(
+            play_match(&mut Solver::new(), &mut Corner),
+            Win {
+                winner: Crosses,
+                reason: IllegalMove
+            }
+        );
+    }
+}
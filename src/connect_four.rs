@@ -0,0 +1,279 @@
+use crate::*;
+use anyhow::Context as _;
+use std::fmt;
+use std::str::FromStr;
+
+const WIDTH: usize = 7;
+const HEIGHT: usize = 6;
+/// Every column has one extra bit on top so that lines can not wrap between columns.
+const COLUMN_BITS: usize = HEIGHT + 1;
+const BOTTOM_ROW: u64 = {
+    let mut bits = 0;
+    let mut col = 0;
+    while col < WIDTH {
+        bits |= 1 << (col * COLUMN_BITS);
+        col += 1;
+    }
+    bits
+};
+const FULL_BOARD: u64 = BOTTOM_ROW * ((1 << HEIGHT) - 1);
+
+/// A move in Connect Four: the column to drop a disc in.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+#[repr(transparent)]
+pub struct Column(usize);
+
+/// A state in Connect Four on a 7×6 board.
+///
+/// The board is stored as one bitboard per player where bit `col * 7 + row` is set if the player
+/// has a disc in that cell, counting rows from the bottom. The topmost bit of every column is
+/// always empty.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub struct ConnectFour {
+    /// discs[p] = the bitboard for player p:
+    discs: [u64; 2],
+}
+
+impl Column {
+    pub const N: usize = WIDTH;
+
+    pub const fn to_usize(&self) -> usize {
+        self.0
+    }
+
+    pub const fn from_usize(i: usize) -> Self {
+        Column(i)
+    }
+
+    /// The bits of all cells in this column.
+    const fn mask(self) -> u64 {
+        ((1 << HEIGHT) - 1) << (self.0 * COLUMN_BITS)
+    }
+}
+
+impl GameMove for Column {
+    const N: usize = Column::N;
+
+    fn to_usize(self) -> usize {
+        Column::to_usize(&self)
+    }
+
+    fn from_usize(i: usize) -> Self {
+        Column::from_usize(i)
+    }
+}
+
+impl fmt::Display for Column {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0 + 1)
+    }
+}
+
+impl FromStr for Column {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let col = s
+            .parse::<usize>()
+            .context("A column should be represented by a number.")?;
+        anyhow::ensure!(
+            (1..=WIDTH).contains(&col),
+            "The column must be in the range [1, {WIDTH}]."
+        );
+        Ok(Column(col - 1))
+    }
+}
+
+/// Check if a bitboard contains four in a line where consecutive cells are `shift` bits apart.
+fn has_four(bits: u64, shift: usize) -> bool {
+    let pairs = bits & (bits >> shift);
+    pairs & (pairs >> (2 * shift)) != 0
+}
+
+impl ConnectFour {
+    /// An empty board.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The bits of all occupied cells.
+    fn occupied(&self) -> u64 {
+        self.discs[0] | self.discs[1]
+    }
+
+    /// Get the mark in a cell, counting rows from the bottom.
+    pub fn get(&self, col: Column, row: usize) -> Mark {
+        assert!(row < HEIGHT);
+        let bit = 1 << (col.to_usize() * COLUMN_BITS + row);
+        if self.discs[Crosses as usize] & bit != 0 {
+            Cross
+        } else if self.discs[Naughts as usize] & bit != 0 {
+            Naught
+        } else {
+            Blank
+        }
+    }
+
+    /// The number of discs in a column.
+    pub fn height(&self, col: Column) -> usize {
+        (self.occupied() & col.mask()).count_ones() as usize
+    }
+
+    /// Check if the given player has four in a row or column.
+    pub fn has_row_or_column(&self, player: Player) -> bool {
+        let bits = self.discs[player as usize];
+        has_four(bits, 1) || has_four(bits, COLUMN_BITS)
+    }
+
+    /// Check if the given player has four on a diagonal.
+    pub fn has_diagonal(&self, player: Player) -> bool {
+        let bits = self.discs[player as usize];
+        has_four(bits, COLUMN_BITS - 1) || has_four(bits, COLUMN_BITS + 1)
+    }
+
+    /// Check if the board is full.
+    pub fn is_draw(&self) -> bool {
+        self.occupied() == FULL_BOARD
+    }
+
+    /// All columns which are not full.  Garanteed to be ordered by `Column::to_usize()`.
+    pub fn legal_moves(&self) -> impl Iterator<Item = Column> + '_ {
+        (0..WIDTH)
+            .map(Column)
+            .filter(|&col| self.height(col) < HEIGHT)
+    }
+
+    /// Drop a disc for the given player in a column.
+    pub fn play(&mut self, m: Column, player: Player) {
+        debug_assert!(self.height(m) < HEIGHT);
+        // Adding the bottom bit of the column to the occupied bits carries up to the first empty
+        // cell.
+        let bottom = 1 << (m.to_usize() * COLUMN_BITS);
+        let new_bit = (self.occupied() + bottom) & m.mask();
+        self.discs[player as usize] |= new_bit;
+    }
+
+    /// Given the player who made the last move, return the result if the game is over.
+    pub fn result(&self, player: Player) -> Option<GameResult> {
+        if self.has_row_or_column(player) {
+            Some(Win {
+                winner: player,
+                reason: RowOrColumn,
+            })
+        } else if self.has_diagonal(player) {
+            Some(Win {
+                winner: player,
+                reason: Diagonal,
+            })
+        } else if self.is_draw() {
+            Some(Draw { reason: BoardFull })
+        } else {
+            None
+        }
+    }
+}
+
+impl Game for ConnectFour {
+    type Move = Column;
+
+    fn new() -> Self {
+        ConnectFour::new()
+    }
+
+    fn ply(&self) -> usize {
+        self.occupied().count_ones() as usize
+    }
+
+    fn legal_moves(&self) -> impl Iterator<Item = Column> {
+        ConnectFour::legal_moves(self)
+    }
+
+    fn play(&mut self, m: Column, player: Player) {
+        ConnectFour::play(self, m, player)
+    }
+
+    fn result(&self, player: Player) -> Option<GameResult> {
+        ConnectFour::result(self, player)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn play_all(moves: &str) -> (ConnectFour, Player) {
+        let mut pos = ConnectFour::new();
+        let mut turn = Crosses;
+        for (i, c) in moves.chars().enumerate() {
+            if i > 0 {
+                assert_eq!(pos.result(turn), None);
+                turn = turn.opponent();
+            }
+            pos.play(c.to_string().parse().unwrap(), turn);
+        }
+        (pos, turn)
+    }
+
+    #[test]
+    fn test_gravity() {
+        let (pos, _) = play_all("4434");
+        assert_eq!(pos.height(Column(3)), 3);
+        assert_eq!(pos.height(Column(2)), 1);
+        assert_eq!(pos.get(Column(3), 0), Cross);
+        assert_eq!(pos.get(Column(3), 1), Naught);
+        assert_eq!(pos.get(Column(3), 2), Naught);
+        assert_eq!(pos.get(Column(2), 0), Cross);
+        assert_eq!(pos.get(Column(2), 1), Blank);
+
+        let (pos, _) = play_all("111111");
+        assert_eq!(pos.legal_moves().count(), Column::N - 1);
+        assert!(pos.legal_moves().all(|m| m != Column(0)));
+    }
+
+    #[test]
+    fn test_result() {
+        let vertical = play_all("1212121");
+        assert_eq!(
+            vertical.0.result(vertical.1),
+            Some(Win {
+                winner: Crosses,
+                reason: RowOrColumn
+            })
+        );
+        let horizontal = play_all("1122334");
+        assert_eq!(
+            horizontal.0.result(horizontal.1),
+            Some(Win {
+                winner: Crosses,
+                reason: RowOrColumn
+            })
+        );
+        let diagonal = play_all("12233434744");
+        assert_eq!(
+            diagonal.0.result(diagonal.1),
+            Some(Win {
+                winner: Crosses,
+                reason: Diagonal
+            })
+        );
+        // Four discs split over the top of one column and the bottom of the next is not a line:
+        assert!(!has_four(0b1011_1000, 1));
+        let (pos, turn) = play_all("112233");
+        assert_eq!(pos.result(turn), None);
+    }
+
+    #[test]
+    fn test_machine_table_limit() {
+        let mut machine: Machine<ConnectFour> = Machine::new();
+        machine.set_max_depth(Some(4));
+        for _ in 0..100 {
+            machine.play_training_match();
+        }
+        assert!(machine.values().keys().all(|pos| Game::ply(pos) < 4));
+        let mut machine: Machine<ConnectFour> = Machine::new();
+        machine.set_max_positions(Some(50));
+        for _ in 0..100 {
+            machine.play_training_match();
+        }
+        assert!(machine.values().len() <= 50);
+    }
+}
@@ -0,0 +1,20 @@
+/// How the factors which the reward gives the moves of a player are passed on to the earlier
+/// moves of the player.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum CreditAssignment {
+    /// Multiply the score of every move by its own factor and the factors of all later moves.
+    Uniform,
+    /// Like `Uniform`, but raise the factor of the move `k` moves later to the power `decay^k`,
+    /// where `k` counts the moves of the player, not the plies of both players.
+    Geometric { decay: f64 },
+    /// Like `Uniform`, but raise the factor of a move `d` plies later to the power `lambda^d`,
+    /// like an eligibility trace which decays over the plies of both players. Positions don't
+    /// repeat within a game in any of the games, so the traces are never summed over repeated
+    /// visits, and with alternating turns this is `Geometric { decay: lambda^2 }`.
+    EligibilityTraces { lambda: f64 },
+    /// Multiply the score of every move by its own factor and the cube root of the factor which
+    /// the other scores of the next move were multiplied with. After a win, that factor is below
+    /// 1.0, so the earlier moves of the winner get lower scores.
+    #[default]
+    CbrtChain,
+}
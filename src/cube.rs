@@ -0,0 +1,337 @@
+use crate::moves::parse_square;
+use crate::*;
+use anyhow::Context as _;
+use std::fmt;
+use std::str::FromStr;
+
+/// The number of lines in the 4×4×4 cube, which is the largest supported size.
+const MAX_LINES: usize = 76;
+
+/// Three dimensional tic-tac-toe on a 3×3×3 cube.
+pub type Cube3 = Cube<3>;
+
+/// Three dimensional tic-tac-toe on a 4×4×4 cube, also known as Qubic.
+pub type Qubic = Cube<4>;
+
+/// A move in three dimensional tic-tac-toe.
+///
+/// Written as the layer number followed by the square on that layer, like "2b3".
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub struct CubeMove<const S: usize> {
+    pub layer: usize,
+    pub row: usize,
+    pub col: usize,
+}
+
+/// A state in three dimensional tic-tac-toe on an S×S×S cube.
+///
+/// The game is won by getting S marks in a line in any direction through the cube.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub struct Cube<const S: usize> {
+    /// marks[p] has bit i set if player p has a mark on the cell with index i:
+    marks: [u64; 2],
+}
+
+/// Compute all lines of `size` cells through a cube as bitmasks.
+///
+/// If `diagonal` is true, return the lines going in more than one dimension, otherwise return the
+/// lines parallel to an axis.
+const fn compute_lines(size: usize, diagonal: bool) -> ([u64; MAX_LINES], usize) {
+    let mut lines = [0; MAX_LINES];
+    let mut n = 0;
+    let size = size as isize;
+    let mut d = 0;
+    while d < 27 {
+        let dir = [d / 9 - 1, d / 3 % 3 - 1, d % 3 - 1];
+        d += 1;
+        // Skip the zero direction and count every line only once by requiring that the first
+        // non-zero component is positive.
+        let first = if dir[0] != 0 {
+            dir[0]
+        } else if dir[1] != 0 {
+            dir[1]
+        } else {
+            dir[2]
+        };
+        let dimensions = (dir[0] != 0) as u32 + (dir[1] != 0) as u32 + (dir[2] != 0) as u32;
+        if first != 1 || (dimensions > 1) != diagonal {
+            continue;
+        }
+        let mut start = 0;
+        'starts: while start < size * size * size {
+            let pos = [start / (size * size), start / size % size, start % size];
+            start += 1;
+            let mut line = 0;
+            let mut i = 0;
+            while i < size {
+                let (l, r, c) = (
+                    pos[0] + i * dir[0],
+                    pos[1] + i * dir[1],
+                    pos[2] + i * dir[2],
+                );
+                if l < 0 || l >= size || r < 0 || r >= size || c < 0 || c >= size {
+                    continue 'starts;
+                }
+                line |= 1 << ((l * size + r) * size + c);
+                i += 1;
+            }
+            lines[n] = line;
+            n += 1;
+        }
+    }
+    (lines, n)
+}
+
+impl<const S: usize> CubeMove<S> {
+    pub const N: usize = S * S * S;
+}
+
+impl<const S: usize> GameMove for CubeMove<S> {
+    const N: usize = Self::N;
+
+    fn to_usize(self) -> usize {
+        (self.layer * S + self.row) * S + self.col
+    }
+
+    fn from_usize(i: usize) -> Self {
+        Self {
+            layer: i / (S * S),
+            row: i / S % S,
+            col: i % S,
+        }
+    }
+}
+
+impl<const S: usize> fmt::Display for CubeMove<S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}{}{}",
+            self.layer + 1,
+            char::from_u32('a' as u32 + self.row as u32).unwrap(),
+            self.col + 1
+        )
+    }
+}
+
+impl<const S: usize> FromStr for CubeMove<S> {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let square_start = s
+            .find(|c: char| !c.is_ascii_digit())
+            .context("A move should be a layer followed by a square, like 2b3.")?;
+        let (layer, square) = s.split_at(square_start);
+        let layer = layer
+            .parse::<usize>()
+            .context("The move layer should be represented by a number.")?;
+        anyhow::ensure!(
+            (1..=S).contains(&layer),
+            "The move layer must be in the range [1, {S}]."
+        );
+        let (row, col) = parse_square(square, S)?;
+        Ok(Self {
+            layer: layer - 1,
+            row,
+            col,
+        })
+    }
+}
+
+impl<const S: usize> Cube<S> {
+    /// The bits of all cells.
+    const ALL: u64 = {
+        assert!(S * S * S <= u64::BITS as usize);
+        u64::MAX >> (u64::BITS as usize - S * S * S)
+    };
+    /// The lines parallel to one of the axes.
+    const STRAIGHT_LINES: ([u64; MAX_LINES], usize) = compute_lines(S, false);
+    /// The lines going in more than one dimension.
+    const DIAGONAL_LINES: ([u64; MAX_LINES], usize) = compute_lines(S, true);
+
+    /// An empty cube.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// All lines parallel to one of the axes.
+    pub fn straight_lines() -> &'static [u64] {
+        &Self::STRAIGHT_LINES.0[..Self::STRAIGHT_LINES.1]
+    }
+
+    /// All lines going in more than one dimension: face diagonals and space diagonals.
+    pub fn diagonal_lines() -> &'static [u64] {
+        &Self::DIAGONAL_LINES.0[..Self::DIAGONAL_LINES.1]
+    }
+
+    /// Get the mark in a cell.
+    pub fn get(&self, m: CubeMove<S>) -> Mark {
+        let bit = 1 << m.to_usize();
+        if self.marks[Crosses as usize] & bit != 0 {
+            Cross
+        } else if self.marks[Naughts as usize] & bit != 0 {
+            Naught
+        } else {
+            Blank
+        }
+    }
+
+    /// Check if the given player has a complete line among `lines`.
+    fn has_line(&self, player: Player, lines: &[u64]) -> bool {
+        let marks = self.marks[player as usize];
+        lines.iter().any(|&line| line & !marks == 0)
+    }
+
+    /// Check if the game is a draw.
+    pub fn is_draw(&self) -> bool {
+        self.marks[0] | self.marks[1] == Self::ALL
+    }
+
+    /// All legal moves in the position.  Garanteed to be ordered by `GameMove::to_usize()`.
+    pub fn legal_moves(&self) -> impl Iterator<Item = CubeMove<S>> + '_ {
+        let occupied = self.marks[0] | self.marks[1];
+        (0..CubeMove::<S>::N)
+            .filter(move |i| occupied & (1 << i) == 0)
+            .map(CubeMove::from_usize)
+    }
+
+    /// Make a move with the given mark.
+    pub fn play(&mut self, m: CubeMove<S>, player: Player) {
+        debug_assert_eq!(self.get(m), Blank);
+        self.marks[player as usize] |= 1 << m.to_usize();
+    }
+
+    /// Given the player who made the last move, return the result if the game is over.
+    pub fn result(&self, player: Player) -> Option<GameResult> {
+        if self.has_line(player, Self::straight_lines()) {
+            Some(Win {
+                winner: player,
+                reason: RowOrColumn,
+            })
+        } else if self.has_line(player, Self::diagonal_lines()) {
+            Some(Win {
+                winner: player,
+                reason: Diagonal,
+            })
+        } else if self.is_draw() {
+            Some(Draw { reason: BoardFull })
+        } else {
+            None
+        }
+    }
+}
+
+impl<const S: usize> Game for Cube<S> {
+    type Move = CubeMove<S>;
+
+    fn new() -> Self {
+        Cube::new()
+    }
+
+    fn ply(&self) -> usize {
+        (self.marks[0] | self.marks[1]).count_ones() as usize
+    }
+
+    fn legal_moves(&self) -> impl Iterator<Item = CubeMove<S>> {
+        Cube::legal_moves(self)
+    }
+
+    fn play(&mut self, m: CubeMove<S>, player: Player) {
+        Cube::play(self, m, player)
+    }
+
+    fn result(&self, player: Player) -> Option<GameResult> {
+        Cube::result(self, player)
+    }
+}
+
+/// Renders the layers side by side, with the first layer to the left.
+impl<const S: usize> fmt::Display for Cube<S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let layer_width = 1 + 2 * S;
+        let titles = (0..S)
+            .map(|layer| format!("{:<layer_width$}", format!("layer {}", layer + 1)))
+            .collect::<Vec<_>>();
+        writeln!(f, "{}", titles.join("  ").trim_end())?;
+        let columns = (1..=S).map(|col| format!(" {col}")).collect::<String>();
+        writeln!(f, "{}", vec![format!(" {columns}"); S].join("  "))?;
+        for row in 0..S {
+            let letter = char::from_u32('a' as u32 + row as u32).unwrap();
+            let layers = (0..S)
+                .map(|layer| {
+                    let cells = (0..S)
+                        .map(|col| format!(" {}", self.get(CubeMove { layer, row, col }).to_char()))
+                        .collect::<String>();
+                    format!("{letter}{cells}")
+                })
+                .collect::<Vec<_>>();
+            writeln!(f, "{}", layers.join("  "))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lines() {
+        assert_eq!(Cube3::straight_lines().len(), 27);
+        assert_eq!(Cube3::diagonal_lines().len(), 22);
+        assert_eq!(Qubic::straight_lines().len(), 48);
+        assert_eq!(Qubic::diagonal_lines().len(), 28);
+        for line in Qubic::straight_lines()
+            .iter()
+            .chain(Qubic::diagonal_lines())
+        {
+            assert_eq!(line.count_ones(), 4);
+        }
+    }
+
+    #[test]
+    fn test_notation() {
+        for i in 0..CubeMove::<4>::N {
+            let m = CubeMove::<4>::from_usize(i);
+            assert_eq!(m.to_string().parse::<CubeMove<4>>().unwrap(), m);
+        }
+        assert_eq!(
+            "2b3".parse::<CubeMove<3>>().unwrap(),
+            CubeMove {
+                layer: 1,
+                row: 1,
+                col: 2
+            }
+        );
+        assert!("4a1".parse::<CubeMove<3>>().is_err());
+        assert!("1d1".parse::<CubeMove<3>>().is_err());
+        assert!("b2".parse::<CubeMove<3>>().is_err());
+    }
+
+    #[test]
+    fn test_space_diagonal() {
+        let mut pos = Qubic::new();
+        for (x, o) in [("1a1", "1a2"), ("2b2", "1a3"), ("3c3", "1a4")] {
+            pos.play(x.parse().unwrap(), Crosses);
+            assert_eq!(pos.result(Crosses), None);
+            pos.play(o.parse().unwrap(), Naughts);
+            assert_eq!(pos.result(Naughts), None);
+        }
+        pos.play("4d4".parse().unwrap(), Crosses);
+        assert_eq!(
+            pos.result(Crosses),
+            Some(Win {
+                winner: Crosses,
+                reason: Diagonal
+            })
+        );
+        assert_eq!(
+            pos.to_string(),
+            "layer 1    layer 2    layer 3    layer 4\n  \
+             1 2 3 4    1 2 3 4    1 2 3 4    1 2 3 4\n\
+             a X O O O  a . . . .  a . . . .  a . . . .\n\
+             b . . . .  b . X . .  b . . . .  b . . . .\n\
+             c . . . .  c . . . .  c . . X .  c . . . .\n\
+             d . . . .  d . . . .  d . . . .  d . . . X\n"
+        );
+    }
+}
@@ -0,0 +1,162 @@
+use crate::*;
+use anyhow::Context as _;
+use std::io::{BufRead as _, BufReader, Write as _};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+/// The time an engine has to get ready after starting, which may include training.
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// An external engine playing tic-tac-toe over the line protocol of `serve`.
+///
+/// The engine runs as a child process which reads commands from stdin and writes responses to
+/// stdout. It loses a game if it does not answer in time, sends a move which is not valid or
+/// breaks the protocol in another way.
+#[derive(Debug)]
+pub struct Engine {
+    child: Child,
+    stdin: ChildStdin,
+    /// Lines written by the engine, read by a separate thread so waiting can time out.
+    lines: Receiver<String>,
+    /// The time the engine has for every move.
+    timeout: Duration,
+}
+
+impl Engine {
+    /// Start an engine and wait until it is ready.
+    pub fn spawn(mut command: Command, timeout: Duration) -> anyhow::Result<Self> {
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("Failed to start the engine {command:?}."))?;
+        let stdin = child.stdin.take().unwrap();
+        let stdout = BufReader::new(child.stdout.take().unwrap());
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in stdout.lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        let mut engine = Self {
+            child,
+            stdin,
+            lines,
+            timeout: STARTUP_TIMEOUT,
+        };
+        engine
+            .synchronize()
+            .map_err(|reason| anyhow::anyhow!("The engine did not get ready: {reason}."))?;
+        engine.timeout = timeout;
+        Ok(engine)
+    }
+
+    /// Send a command. An engine which does not read its commands forfeits.
+    fn send(&mut self, request: Request) -> Result<(), WinReason> {
+        writeln!(self.stdin, "{request}")
+            .and_then(|()| self.stdin.flush())
+            .map_err(|_| Forfeit)
+    }
+
+    /// Wait for the next line written by the engine.
+    fn receive(&mut self) -> Result<String, WinReason> {
+        self.lines.recv_timeout(self.timeout).map_err(|e| match e {
+            RecvTimeoutError::Timeout => Timeout,
+            RecvTimeoutError::Disconnected => Forfeit,
+        })
+    }
+
+    /// Send `isready` and skip responses until `readyok`, like a late answer to a previous move.
+    fn synchronize(&mut self) -> Result<(), WinReason> {
+        self.send(Request::IsReady)?;
+        while self.receive()?.parse::<Response>().ok() != Some(Response::ReadyOk) {}
+        Ok(())
+    }
+}
+
+impl Agent for Engine {
+    fn select_move(&mut self, pos: State, _turn: Player) -> Result<Option<Move>, WinReason> {
+        self.send(Request::SetPosition {
+            start: pos,
+            moves: Vec::new(),
+        })?;
+        self.send(Request::Go)?;
+        let line = self.receive()?;
+        match line.parse() {
+            Ok(Response::BestMove(m)) => Ok(m),
+            Err(_) if line.starts_with("bestmove ") => Err(IllegalMove),
+            _ => Err(Forfeit),
+        }
+    }
+
+    fn new_game(&mut self) {
+        // Errors show up as a forfeit when the engine has to move.
+        let _ = self
+            .synchronize()
+            .and_then(|()| self.send(Request::NewGame));
+    }
+
+    fn game_over(&mut self, result: GameResult) {
+        let _ = self.send(Request::GameOver(result));
+    }
+}
+
+impl Drop for Engine {
+    fn drop(&mut self) {
+        let _ = self.send(Request::Quit);
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    /// An engine written in shell which answers `go` with `answer`.
+    fn shell_engine(answer: &str) -> anyhow::Result<Engine> {
+        let script = format!(
+            "while read line; do case \"$line\" in \
+             isready) echo readyok;; go) {answer};; esac; done"
+        );
+        let mut command = Command::new("sh");
+        command.args(["-c", &script]);
+        Engine::spawn(command, Duration::from_millis(500))
+    }
+
+    #[test]
+    fn test_engine() {
+        let pos = State::new();
+        let mut engine = shell_engine("echo bestmove b2").unwrap();
+        assert_eq!(
+            engine.select_move(pos, Crosses),
+            Ok(Some("b2".parse().unwrap()))
+        );
+        let mut engine = shell_engine("echo bestmove resign").unwrap();
+        assert_eq!(engine.select_move(pos, Crosses), Ok(None));
+
+        for (answer, reason) in [
+            ("echo bestmove z9", IllegalMove),
+            ("sleep 2", Timeout),
+            ("echo readyok", Forfeit),
+            ("exit", Forfeit),
+        ] {
+            let mut engine = shell_engine(answer).unwrap();
+            assert_eq!(engine.select_move(pos, Crosses), Err(reason), "{answer}");
+        }
+
+        let mut engine = shell_engine("echo bestmove a1").unwrap();
+        assert_eq!(
+            play_match(&mut Solver::new(), &mut engine),
+            Win {
+                winner: Crosses,
+                reason: IllegalMove
+            }
+        );
+        assert!(Engine::spawn(Command::new("true"), Duration::from_millis(500)).is_err());
+    }
+}
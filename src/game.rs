@@ -0,0 +1,86 @@
+use crate::*;
+use std::fmt;
+use std::hash::Hash;
+
+/// A move which can be mapped to an index in the range `[0, N)`.
+pub trait GameMove: Copy + Eq + Hash + fmt::Debug + fmt::Display {
+    /// The number of distinct moves.
+    const N: usize;
+
+    fn to_usize(self) -> usize;
+
+    fn from_usize(i: usize) -> Self;
+}
+
+/// A two player game which the machine can learn.
+pub trait Game: Copy + Eq + Hash + fmt::Debug {
+    type Move: GameMove;
+
+    /// The initial position.
+    fn new() -> Self;
+
+    /// The number of moves made since the initial position.
+    fn ply(&self) -> usize;
+
+    /// All legal moves in the position.  Must be ordered by `GameMove::to_usize()`.
+    fn legal_moves(&self) -> impl Iterator<Item = Self::Move>;
+
+    /// Let `player` make a move.
+    fn play(&mut self, m: Self::Move, player: Player);
+
+    /// Given the player who made the last move, return the result if the game is over.
+    fn result(&self, player: Player) -> Option<GameResult>;
+
+    /// The distinct images of the position and a move under the symmetries of the game,
+    /// starting with the position and the move themselves.
+    fn symmetric_images(&self, m: Self::Move) -> Vec<(Self, Self::Move)> {
+        vec![(*self, m)]
+    }
+}
+
+impl GameMove for Move {
+    const N: usize = Move::N;
+
+    fn to_usize(self) -> usize {
+        Move::to_usize(&self)
+    }
+
+    fn from_usize(i: usize) -> Self {
+        Move::from_usize(i)
+    }
+}
+
+impl Game for State {
+    type Move = Move;
+
+    fn new() -> Self {
+        State::new()
+    }
+
+    fn ply(&self) -> usize {
+        Move::all().filter(|&m| self[m] != Blank).count()
+    }
+
+    fn legal_moves(&self) -> impl Iterator<Item = Move> {
+        State::legal_moves(self).iter()
+    }
+
+    fn play(&mut self, m: Move, player: Player) {
+        State::play(self, m, player)
+    }
+
+    fn result(&self, player: Player) -> Option<GameResult> {
+        State::result(self, player)
+    }
+
+    fn symmetric_images(&self, m: Move) -> Vec<(State, Move)> {
+        let mut images = Vec::new();
+        for sym in Symmetry::all() {
+            let image = (sym.apply(self), sym.apply_move(m));
+            if !images.contains(&image) {
+                images.push(image);
+            }
+        }
+        images
+    }
+}
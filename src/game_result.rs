@@ -1,9 +1,10 @@
 use crate::Player;
+use std::fmt;
 
 /// The result of a game.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum GameResult {
-    Draw,
+    Draw { reason: DrawReason },
     Win { winner: Player, reason: WinReason },
 }
 pub use GameResult::*;
@@ -11,11 +12,78 @@ pub use GameResult::*;
 /// A reason for winning a game.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum WinReason {
-    /// Three marks in a row or column.
+    /// A line of marks in a row or column.
     RowOrColumn,
-    /// Three marks on the diagonal.
+    /// A line of marks on a diagonal.
     Diagonal,
     /// Opponent resigned.
     Resignation,
+    /// Opponent did not move in time.
+    Timeout,
+    /// Opponent made a move which is not legal in the position.
+    IllegalMove,
+    /// Opponent broke the rules in another way, like an external engine breaking the protocol.
+    Forfeit,
 }
 pub use WinReason::*;
+
+/// A reason for a drawn game.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum DrawReason {
+    /// No player can make a line anymore, usually because the board is full.
+    BoardFull,
+    /// The players agreed to a draw.
+    Agreement,
+    /// The game was stopped and declared a draw, like after a move limit in a tournament.
+    Adjudication,
+}
+pub use DrawReason::*;
+
+impl WinReason {
+    pub const ALL: [WinReason; 6] = [
+        RowOrColumn,
+        Diagonal,
+        Resignation,
+        Timeout,
+        IllegalMove,
+        Forfeit,
+    ];
+}
+
+impl DrawReason {
+    pub const ALL: [DrawReason; 3] = [BoardFull, Agreement, Adjudication];
+}
+
+impl fmt::Display for WinReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            RowOrColumn => "row or column",
+            Diagonal => "diagonal",
+            Resignation => "resignation",
+            Timeout => "timeout",
+            IllegalMove => "illegal move",
+            Forfeit => "forfeit",
+        };
+        write!(f, "{s}")
+    }
+}
+
+impl fmt::Display for DrawReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            BoardFull => "full board",
+            Agreement => "agreement",
+            Adjudication => "adjudication",
+        };
+        write!(f, "{s}")
+    }
+}
+
+impl fmt::Display for GameResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Draw { reason } => write!(f, "Draw by {reason}"),
+            Win { winner, reason } => write!(f, "{winner} won by {reason}"),
+        }
+    }
+}
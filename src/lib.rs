@@ -1,10 +1,48 @@
+mod agent;
+mod connect_four;
+mod credit;
+mod cube;
+mod engine;
+mod game;
 mod game_result;
 mod machine;
 mod move_scores;
 mod moves;
+mod numerical;
+mod position;
+mod protocol;
+mod render;
+mod reward;
+mod selection;
+mod solver;
+mod stats;
+mod symmetry;
 mod tic_tac_toe;
+mod tree;
+mod tui;
+mod ultimate;
+mod wild;
+pub use agent::*;
+pub use connect_four::*;
+pub use credit::CreditAssignment;
+pub use cube::*;
+pub use engine::Engine;
+pub use game::*;
 pub use game_result::*;
-pub use machine::Machine;
-pub use move_scores::MoveScores;
+pub use machine::{Machine, ResignPolicy};
+pub use move_scores::{MoveScores, MoveStats};
 pub use moves::*;
+pub use numerical::*;
+pub use position::Position;
+pub use protocol::*;
+pub use render::BoardView;
+pub use reward::{GameRecord, Reward, ShapedReward};
+pub use selection::{Schedule, Selection};
+pub use solver::*;
+pub use stats::ResultStats;
+pub use symmetry::Symmetry;
 pub use tic_tac_toe::*;
+pub use tree::*;
+pub use tui::run as run_tui;
+pub use ultimate::*;
+pub use wild::*;
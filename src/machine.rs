@@ -1,78 +1,289 @@
 use crate::*;
-use getset::Getters;
+use getset::{CopyGetters, Getters, Setters};
 use rand::prelude::*;
 use std::collections::HashMap;
+use std::fmt;
 
-const DECISIVE_FACTOR: f64 = 32.0;
-const DRAW_FACTOR: f64 = 0.9;
+/// The default lowest score of a legal move.
+const MIN_SCORE: f64 = 1e-9;
 
 const RAND_SEED: u64 = 42;
 
-/// The machine playing tic-tac-toe.
-#[derive(Debug, Getters)]
-pub struct Machine {
+/// When the machine resigns instead of making a move.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum ResignPolicy {
+    /// Always make a move.
+    #[default]
+    Never,
+    /// Resign when the highest score in a position is below a threshold.
+    BestScoreBelow(f64),
+    /// Resign when the solver finds that the position is lost with perfect play. Only feasible
+    /// for small games like tic-tac-toe.
+    SolverLost,
+}
+
+/// The machine playing tic-tac-toe or any other `Game`.
+#[derive(Getters, CopyGetters, Setters)]
+pub struct Machine<G: Game = State> {
     #[getset(get = "pub")]
-    values: HashMap<State, MoveScores>,
+    values: HashMap<G, MoveScores<G::Move>>,
+    /// Positions at this ply or later are not stored in `values` but played randomly.
+    #[getset(get_copy = "pub", set = "pub")]
+    max_depth: Option<usize>,
+    /// When `values` has this many positions, new positions are played randomly.
+    #[getset(get_copy = "pub", set = "pub")]
+    max_positions: Option<usize>,
+    /// Scores of legal moves are kept at least this high, so they don't underflow. Must be
+    /// below `1.0 / G::Move::N`, so that it fits the positions with the most legal moves.
+    #[getset(get_copy = "pub")]
+    min_score: f64,
+    /// How moves are selected by `select_move`, like when playing against humans.
+    #[getset(get_copy = "pub", set = "pub")]
+    selection: Selection,
+    /// How moves are selected in training matches, depending on the number of matches played.
+    #[getset(get = "pub", set = "pub")]
+    training_schedule: Schedule,
+    /// The number of training matches played.
+    #[getset(get_copy = "pub")]
+    training_games: u64,
+    /// When to resign, both in training and when playing with `select_move`.
+    #[getset(get_copy = "pub", set = "pub")]
+    resign_policy: ResignPolicy,
+    /// How the reward of a training match is distributed over the moves of a player.
+    #[getset(get_copy = "pub", set = "pub")]
+    credit_assignment: CreditAssignment,
+    /// Also apply every update after a training match to the symmetric images of the position
+    /// and the move, while `values` keeps storing the positions as they were played.
+    #[getset(get_copy = "pub", set = "pub")]
+    symmetric_updates: bool,
+    /// The factors for the moves of both players after a training match.
+    reward: Box<dyn Reward<G>>,
+    /// Used by `ResignPolicy::SolverLost`, created the first time it is needed since it caches
+    /// the outcomes of all positions it has seen.
+    solver: Option<Solver<G>>,
+    /// The seed of `rng`, to reproduce the training.
+    #[getset(get_copy = "pub")]
+    seed: u64,
+    /// Used unless the caller supplies a random number generator.
     rng: StdRng,
 }
 
-impl Machine {
+/// Leaves out the solver, which only caches outcomes for `ResignPolicy::SolverLost`.
+impl<G: Game> fmt::Debug for Machine<G> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Machine")
+            .field("values", &self.values)
+            .field("max_depth", &self.max_depth)
+            .field("max_positions", &self.max_positions)
+            .field("min_score", &self.min_score)
+            .field("selection", &self.selection)
+            .field("training_schedule", &self.training_schedule)
+            .field("training_games", &self.training_games)
+            .field("resign_policy", &self.resign_policy)
+            .field("credit_assignment", &self.credit_assignment)
+            .field("symmetric_updates", &self.symmetric_updates)
+            .field("reward", &self.reward)
+            .field("seed", &self.seed)
+            .field("rng", &self.rng)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<G: Game> Default for Machine<G> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<G: Game> Machine<G> {
     pub fn new() -> Self {
+        Self::with_seed(RAND_SEED)
+    }
+
+    /// A machine which seeds its random number generator with `seed`.
+    pub fn with_seed(seed: u64) -> Self {
         Self {
             values: HashMap::new(),
-            rng: StdRng::seed_from_u64(RAND_SEED),
+            max_depth: None,
+            max_positions: None,
+            min_score: MIN_SCORE,
+            selection: Selection::default(),
+            training_schedule: Schedule::default(),
+            training_games: 0,
+            resign_policy: ResignPolicy::default(),
+            credit_assignment: CreditAssignment::default(),
+            symmetric_updates: false,
+            reward: Box::new(ShapedReward::default()),
+            solver: None,
+            seed,
+            rng: StdRng::seed_from_u64(seed),
         }
     }
 
+    /// Set `min_score`, which must be in the range `[0, 1.0 / G::Move::N)`.
+    pub fn set_min_score(&mut self, min_score: f64) -> &mut Self {
+        assert!(
+            (0.0..1.0 / G::Move::N as f64).contains(&min_score),
+            "The minimum score should be at least 0 and below 1 / {}.",
+            G::Move::N
+        );
+        self.min_score = min_score;
+        self
+    }
+
+    pub fn reward(&self) -> &dyn Reward<G> {
+        &*self.reward
+    }
+
+    /// Set the reward for training matches, like a `ShapedReward` which prefers quick wins.
+    pub fn set_reward(&mut self, reward: impl Reward<G> + 'static) {
+        self.reward = Box::new(reward);
+    }
+
     /// Get the move scores for a position.
-    pub fn get_move_scores(&mut self, pos: State) -> &MoveScores {
+    pub fn get_move_scores(&mut self, pos: G) -> &MoveScores<G::Move> {
         self.values
             .entry(pos)
             .or_insert_with(|| MoveScores::initial(&pos))
     }
 
-    /// Select a move for a position.
-    pub fn select_move(&mut self, pos: State) -> Option<Move> {
-        let moves = self
-            .values
-            .entry(pos)
-            .or_insert_with(|| MoveScores::initial(&pos));
-        if moves.all_zero() {
+    /// The average entropy of the scores in bits, weighted by how often the positions were
+    /// visited in training. 0.0 before any training.
+    pub fn average_entropy(&self) -> f64 {
+        let visits = self.values.values().map(|s| s.visits()).sum::<u64>();
+        if visits == 0 {
+            return 0.0;
+        }
+        self.values
+            .values()
+            .map(|s| s.visits() as f64 * s.entropy())
+            .sum::<f64>()
+            / visits as f64
+    }
+
+    /// The average Kullback-Leibler divergence in bits of the scores of `other` from these
+    /// scores, weighted by how often this machine visited the positions in training. Positions
+    /// which `other` has not seen have the initial scores.
+    pub fn kl_divergence(&self, other: &Self) -> f64 {
+        let visits = self.values.values().map(|s| s.visits()).sum::<u64>();
+        if visits == 0 {
+            return 0.0;
+        }
+        self.values
+            .iter()
+            .filter(|(_, scores)| scores.visits() > 0)
+            .map(|(pos, scores)| {
+                let divergence = match other.values.get(pos) {
+                    Some(other_scores) => scores.kl_divergence(other_scores),
+                    None => scores.kl_divergence(&MoveScores::initial(pos)),
+                };
+                scores.visits() as f64 * divergence
+            })
+            .sum::<f64>()
+            / visits as f64
+    }
+
+    /// Check if a position may be stored in `values` according to `max_depth` and
+    /// `max_positions`.
+    fn is_tabular(&self, pos: &G) -> bool {
+        self.values.contains_key(pos)
+            || (self.max_depth.is_none_or(|depth| pos.ply() < depth)
+                && self
+                    .max_positions
+                    .is_none_or(|size| self.values.len() < size))
+    }
+
+    /// The move scores for a position if it may be stored in `values`.
+    fn tabular_scores(&mut self, pos: G) -> Option<&mut MoveScores<G::Move>> {
+        self.is_tabular(&pos).then(|| {
+            self.values
+                .entry(pos)
+                .or_insert_with(|| MoveScores::initial(&pos))
+        })
+    }
+
+    /// Run `f` with the machine's own random number generator.
+    fn with_own_rng<T>(&mut self, f: impl FnOnce(&mut Self, &mut StdRng) -> T) -> T {
+        let mut rng = std::mem::replace(&mut self.rng, StdRng::from_seed(Default::default()));
+        let res = f(self, &mut rng);
+        self.rng = rng;
+        res
+    }
+
+    /// Select a move for a position according to `selection`, or return `None` to resign
+    /// according to `resign_policy`.
+    pub fn select_move(&mut self, pos: G) -> Option<G::Move> {
+        self.with_own_rng(|machine, rng| machine.select_move_with(rng, pos))
+    }
+
+    /// Like `select_move` but with a random number generator supplied by the caller.
+    pub fn select_move_with(&mut self, rng: &mut impl Rng, pos: G) -> Option<G::Move> {
+        if self.resigns(pos) {
             return None;
         }
-        let mut x = self.rng.gen::<f64>();
-        let mut i = Move::N - 1;
-        loop {
-            let m = moves.move_at()[i];
-            let m_score = moves.score()[m.to_usize()];
-            if m_score == 0.0 {
-                i -= 1;
-                continue;
+        self.select_move_by(rng, pos, self.selection)
+    }
+
+    /// Check if the machine resigns in a position according to `resign_policy`.
+    fn resigns(&mut self, pos: G) -> bool {
+        match self.resign_policy {
+            ResignPolicy::Never => false,
+            ResignPolicy::BestScoreBelow(threshold) => {
+                self.is_tabular(&pos) && {
+                    let scores = self.get_move_scores(pos);
+                    scores.best().is_some_and(|m| scores.score(m) < threshold)
+                }
             }
-            if x < m_score {
-                break Some(m);
+            ResignPolicy::SolverLost => {
+                let solver = self.solver.get_or_insert_with(Solver::new);
+                solver.outcome(pos, turn_at(pos.ply())) == Lost
             }
-            x -= m_score;
-            assert_ne!(i, 0);
-            i -= 1;
         }
     }
 
+    fn select_move_by(
+        &mut self,
+        rng: &mut impl Rng,
+        pos: G,
+        selection: Selection,
+    ) -> Option<G::Move> {
+        if !self.is_tabular(&pos) {
+            return pos.legal_moves().choose(rng);
+        }
+        self.values
+            .entry(pos)
+            .or_insert_with(|| MoveScores::initial(&pos))
+            .select(selection, rng)
+    }
+
     /// Let the machine play a training match against itself and update scores accordingly.
     pub fn play_training_match(&mut self) -> GameResult {
-        let mut pos = State::new();
+        self.with_own_rng(|machine, rng| machine.play_training_match_with(rng))
+    }
+
+    /// Like `play_training_match` but with a random number generator supplied by the caller.
+    pub fn play_training_match_with(&mut self, rng: &mut impl Rng) -> GameResult {
+        let selection = self.training_schedule.at(self.training_games);
+        self.training_games += 1;
+        let mut pos = G::new();
 
-        // moves[p] is the moves played by player p:
-        let mut moves = [Vec::new(), Vec::new()];
+        // The moves played by both players in order:
+        let mut moves = Vec::new();
+        let mut resignation = None;
         let mut turn = Crosses;
         let result = loop {
-            let Some(m) = self.select_move(pos) else {
+            let resigns = self.resigns(pos);
+            let m = self.select_move_by(rng, pos, selection);
+            let Some(m) = m.filter(|_| !resigns) else {
+                // The move which would have been played is updated like a move before a loss, so
+                // the scores of the resigning position change like after any other loss.
+                resignation = m.map(|m| (pos, m));
                 break Win {
                     winner: turn.opponent(),
                     reason: Resignation,
                 };
             };
-            moves[turn as usize].push((pos, m));
+            moves.push((pos, m));
             pos.play(m, turn);
             if let Some(res) = pos.result(turn) {
                 break res;
@@ -81,23 +292,370 @@ impl Machine {
         };
 
         // Update scores.
-        let factors: [(Player, f64); 2] = match result {
-            Draw => [(Crosses, DRAW_FACTOR), (Naughts, DRAW_FACTOR)],
-            Win { winner, .. } => [
-                (winner, DECISIVE_FACTOR),
-                (winner.opponent(), 1.0 / DECISIVE_FACTOR),
-            ],
+        let mut record = GameRecord::new(moves, result);
+        record.set_resignation(resignation);
+        for player in [Crosses, Naughts] {
+            let moves = record.moves_of(player);
+            let factors = self.reward.factors(&record, player);
+            assert_eq!(
+                factors.len(),
+                moves.len(),
+                "There should be a factor for every move."
+            );
+            // The last move of a resigning player was not played.
+            let resigned = record.resigned(player);
+            self.assign_credit(&moves, &factors, player, result, resigned);
+        }
+        result
+    }
+
+    /// Record the result for the moves of a player in order, and multiply their scores by the
+    /// factors of the reward combined according to `credit_assignment`. When the player
+    /// `resigned`, the last move was not played, so its score is multiplied but the result is
+    /// not recorded for it.
+    fn assign_credit(
+        &mut self,
+        moves: &[(G, G::Move)],
+        factors: &[f64],
+        player: Player,
+        result: GameResult,
+        resigned: bool,
+    ) {
+        let min_score = self.min_score;
+        // The logarithm of the factors of this and later moves, decayed except for CbrtChain:
+        let mut later = 0.0;
+        // The ply of the next move of the player, for EligibilityTraces:
+        let mut next_ply = None;
+        // The cube root of the factor of the other scores of the next move for CbrtChain:
+        let mut chain = 1.0;
+        for (k, (&(pos, m), &own)) in moves.iter().zip(factors).rev().enumerate() {
+            let factor = match self.credit_assignment {
+                CreditAssignment::Uniform => {
+                    later += own.ln();
+                    later.exp()
+                }
+                CreditAssignment::Geometric { decay } => {
+                    later = own.ln() + decay * later;
+                    later.exp()
+                }
+                CreditAssignment::EligibilityTraces { lambda } => {
+                    let plies = next_ply.map_or(0, |next| next - pos.ply());
+                    later = own.ln() + lambda.powi(plies as i32) * later;
+                    later.exp()
+                }
+                CreditAssignment::CbrtChain => own * chain,
+            };
+            next_ply = Some(pos.ply());
+            // Positions outside of the table were played randomly.
+            let Some(scores) = self.values.get_mut(&pos) else {
+                continue;
+            };
+            if !(resigned && k == 0) {
+                scores.record(m, player, result);
+            }
+            let images = if self.symmetric_updates {
+                pos.symmetric_images(m)
+            } else {
+                vec![(pos, m)]
+            };
+            // The images are multiplied like the move itself. Some images may be other moves in
+            // the same position, so the factor of the other scores in the position is the
+            // product over all of them, which doesn't depend on their order.
+            let mut others = 1.0;
+            for (image, n) in images {
+                if let Some(scores) = self.tabular_scores(image) {
+                    let total_factor = scores.multiply(n, factor, min_score);
+                    if image == pos {
+                        others *= total_factor;
+                    }
+                }
+            }
+            chain = others.cbrt();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resign_policy() {
+        let lost = "XO./X../... o".parse::<State>().unwrap();
+        let drawn = "X../.O./... x".parse::<State>().unwrap();
+        let mut machine = Machine::new();
+        assert!(machine.select_move(lost).is_some());
+        assert!(machine.solver.is_none());
+        machine.set_resign_policy(ResignPolicy::SolverLost);
+        assert_eq!(machine.select_move(lost), None);
+        assert!(machine.solver.is_some());
+        assert!(machine.select_move(drawn).is_some());
+
+        // All 7 moves have a score of 1 / 7 in a new position.
+        machine.set_resign_policy(ResignPolicy::BestScoreBelow(0.1));
+        assert!(machine.select_move(drawn).is_some());
+        machine.set_resign_policy(ResignPolicy::BestScoreBelow(0.2));
+        assert_eq!(machine.select_move(drawn), None);
+
+        // The resigning position is updated like after a loss.
+        let mut machine = Machine::<State>::new();
+        machine.set_resign_policy(ResignPolicy::BestScoreBelow(1.0));
+        assert_eq!(
+            machine.play_training_match(),
+            Win {
+                winner: Naughts,
+                reason: Resignation
+            }
+        );
+        let scores = machine.get_move_scores(State::new());
+        assert_eq!(scores.adjusted(), 2);
+        assert!(scores.score(scores.ranked()[8]) < 1.0 / 9.0);
+        // The move which was not played is not recorded.
+        assert_eq!(scores.visits(), 0);
+        assert!(Move::all().all(|m| scores.move_stats(m).visits() == 0));
+    }
+
+    #[test]
+    #[should_panic(expected = "The minimum score should be at least 0 and below 1 / 9.")]
+    fn test_min_score() {
+        let mut machine = Machine::<State>::new();
+        machine.set_min_score(0.05);
+        machine.set_min_score(0.2);
+    }
+
+    #[test]
+    fn test_seed() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut machine = Machine::<State>::new();
+        let with_rng = (0..100)
+            .map(|_| machine.play_training_match_with(&mut rng))
+            .collect::<Vec<_>>();
+        let games = |mut machine: Machine| {
+            (0..100)
+                .map(|_| machine.play_training_match())
+                .collect::<Vec<_>>()
         };
-        for (player, mut factor) in factors {
-            for (pos, m) in moves[player as usize].iter().rev() {
-                factor = self
-                    .values
-                    .get_mut(pos)
-                    .unwrap()
-                    .multiply(*m, factor)
-                    .cbrt();
+        assert_eq!(Machine::<State>::with_seed(7).seed(), 7);
+        assert_eq!(games(Machine::with_seed(7)), with_rng);
+        assert_ne!(games(Machine::with_seed(8)), with_rng);
+    }
+
+    #[test]
+    fn test_visits() {
+        let mut machine = Machine::<State>::new();
+        let results = (0..1000)
+            .map(|_| machine.play_training_match())
+            .collect::<Vec<_>>();
+        let scores = machine.get_move_scores(State::new());
+        assert_eq!(scores.visits(), 1000);
+        let stats = Move::all()
+            .map(|m| scores.move_stats(m))
+            .collect::<Vec<_>>();
+        let wins = results
+            .iter()
+            .filter(|r| {
+                matches!(
+                    r,
+                    Win {
+                        winner: Crosses,
+                        ..
+                    }
+                )
+            })
+            .count() as u64;
+        assert_eq!(stats.iter().map(|s| s.visits()).sum::<u64>(), 1000);
+        assert_eq!(stats.iter().map(|s| s.wins()).sum::<u64>(), wins);
+    }
+
+    #[test]
+    fn test_reward() {
+        /// A reward which never changes the scores.
+        #[derive(Debug)]
+        struct Neutral;
+
+        impl Reward for Neutral {
+            fn factors(&self, record: &GameRecord<State>, player: Player) -> Vec<f64> {
+                vec![1.0; record.moves_of(player).len()]
             }
         }
-        result
+
+        let mut machine = Machine::<State>::new();
+        machine.set_reward(Neutral);
+        for _ in 0..100 {
+            machine.play_training_match();
+        }
+        let scores = machine.get_move_scores(State::new());
+        assert_eq!(scores.visits(), 100);
+        assert!(Move::all().all(|m| (scores.score(m) - 1.0 / 9.0).abs() < 1e-12));
+    }
+
+    #[test]
+    fn test_credit_assignment() {
+        // Crosses wins with a1, a2, a3 against b1, b2.
+        let mut pos = State::new();
+        let mut moves = Vec::new();
+        for (i, m) in ["a1", "b1", "a2", "b2", "a3"].iter().enumerate() {
+            let m = m.parse().unwrap();
+            if i % 2 == 0 {
+                moves.push((pos, m));
+            }
+            pos.play(m, if i % 2 == 0 { Crosses } else { Naughts });
+        }
+        let result = pos.result(Crosses).unwrap();
+        // The score of a move after multiplying it with `factor` among `n` equal scores.
+        let score = |n: f64, factor: f64| factor / (n - 1.0 + factor);
+        let check = |credit_assignment, factors: [f64; 3], expected: [f64; 3]| {
+            let mut machine = Machine::new();
+            machine.set_credit_assignment(credit_assignment);
+            for &(pos, _) in &moves {
+                machine.get_move_scores(pos);
+            }
+            machine.assign_credit(&moves, &factors, Crosses, result, false);
+            for (&(pos, m), expected) in moves.iter().zip(expected) {
+                let actual = machine.get_move_scores(pos).score(m);
+                assert!((actual - expected).abs() < 1e-12, "{credit_assignment:?}");
+            }
+        };
+
+        // The reward is a factor for the last move, like from `ShapedReward`.
+        let reward = [1.0, 1.0, 32.0];
+        check(
+            CreditAssignment::Uniform,
+            reward,
+            [score(9.0, 32.0), score(7.0, 32.0), score(5.0, 32.0)],
+        );
+        // A reward can also pick out an earlier move, which is combined with the later factors.
+        check(
+            CreditAssignment::Uniform,
+            [0.5, 1.0, 32.0],
+            [score(9.0, 16.0), score(7.0, 32.0), score(5.0, 32.0)],
+        );
+        check(
+            CreditAssignment::Geometric { decay: 0.5 },
+            reward,
+            [
+                score(9.0, 32f64.powf(0.25)),
+                score(7.0, 32f64.powf(0.5)),
+                score(5.0, 32.0),
+            ],
+        );
+        // Moves of crosses are two plies apart, so this is like a decay of 0.25 per move.
+        check(
+            CreditAssignment::EligibilityTraces { lambda: 0.5 },
+            reward,
+            [
+                score(9.0, 32f64.powf(0.0625)),
+                score(7.0, 32f64.powf(0.25)),
+                score(5.0, 32.0),
+            ],
+        );
+        // The other four scores of the last move are multiplied by 5 / 36, so the earlier moves
+        // of the winner are punished.
+        let second = (5.0f64 / 36.0).cbrt();
+        let first = (7.0 / (6.0 + second)).cbrt();
+        check(
+            CreditAssignment::CbrtChain,
+            reward,
+            [score(9.0, first), score(7.0, second), score(5.0, 32.0)],
+        );
+        check(
+            CreditAssignment::CbrtChain,
+            [2.0, 1.0, 32.0],
+            [
+                score(9.0, 2.0 * first),
+                score(7.0, second),
+                score(5.0, 32.0),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_symmetric_updates() {
+        let a1 = "a1".parse().unwrap();
+        let c3 = "c3".parse().unwrap();
+        let images = State::new().symmetric_images(a1);
+        assert_eq!(images.len(), 4);
+        assert!(images.iter().all(|&(pos, _)| pos == State::new()));
+
+        let mut machine = Machine::<State>::new();
+        machine.set_symmetric_updates(true);
+        for _ in 0..100 {
+            machine.play_training_match();
+        }
+        let scores = machine.get_move_scores(State::new());
+        assert!((scores.score(a1) - scores.score(c3)).abs() < 1e-12);
+
+        // Images of positions which were not played are stored as they are.
+        let pos = "X../.../... o".parse::<State>().unwrap();
+        let image = "..X/.../... o".parse::<State>().unwrap();
+        assert!(machine.values().contains_key(&pos));
+        assert!(machine.values().contains_key(&image));
+        let m = "b2".parse().unwrap();
+        assert_eq!(
+            machine.get_move_scores(pos).score(m),
+            machine.get_move_scores(image).score(m)
+        );
+    }
+
+    #[test]
+    fn test_symmetric_chain() {
+        // Naughts plays the center and then an edge in a position which is symmetric along both
+        // diagonals, so the edge has four images in the same position.
+        let mut pos = State::new();
+        let mut moves = Vec::new();
+        for (i, m) in ["a1", "b2", "c3", "b1"].iter().enumerate() {
+            let m = m.parse().unwrap();
+            if i % 2 == 1 {
+                moves.push((pos, m));
+            }
+            pos.play(m, if i % 2 == 0 { Crosses } else { Naughts });
+        }
+        let (symmetric, edge) = moves[1];
+        assert_eq!(symmetric.symmetric_images(edge).len(), 8);
+        assert_eq!(
+            symmetric
+                .symmetric_images(edge)
+                .iter()
+                .filter(|&&(image, _)| image == symmetric)
+                .count(),
+            4
+        );
+
+        let mut machine = Machine::new();
+        machine.set_symmetric_updates(true);
+        for &(pos, _) in &moves {
+            machine.get_move_scores(pos);
+        }
+        let loss = 1.0 / 32.0;
+        let result = Win {
+            winner: Crosses,
+            reason: RowOrColumn,
+        };
+        machine.assign_credit(&moves, &[1.0, loss], Naughts, result, false);
+
+        // All four edges get a factor of 1 / 32 and the two corners are scaled up to match.
+        let scores = machine.get_move_scores(symmetric);
+        assert!((scores.score(edge) - loss / (4.0 * loss + 2.0)).abs() < 1e-12);
+        let chain = (6.0 / (4.0 * loss + 2.0)).cbrt();
+        let (first, center) = moves[0];
+        let expected = chain / (7.0 + chain);
+        assert!((machine.get_move_scores(first).score(center) - expected).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_entropy() {
+        let mut machine = Machine::<State>::new();
+        assert_eq!(machine.average_entropy(), 0.0);
+        machine.play_training_match();
+        // After one game, the scores of the visited positions are still far from converged.
+        let initial_entropy = machine.average_entropy();
+        assert!(initial_entropy > 2.0);
+        let mut trained = Machine::<State>::with_seed(1);
+        for _ in 0..10000 {
+            trained.play_training_match();
+        }
+        assert!(trained.average_entropy() < initial_entropy);
+        assert_eq!(trained.kl_divergence(&trained), 0.0);
+        assert!(trained.kl_divergence(&machine) > 0.1);
     }
 }
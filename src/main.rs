@@ -1,113 +1,329 @@
+use anyhow::Context as _;
 use menace::*;
-use std::collections::HashMap;
-use std::io::{self, Write as _};
-use std::ops::AddAssign;
+use std::io::{self, IsTerminal as _, Write as _};
+use std::str::FromStr;
+use std::time::Duration;
 
 const TRAIN_CYCLES: u32 = 10000000;
 const TRAIN_CHUNKS: u32 = 4;
 const TRAIN_CHUNK_SIZE: u32 = TRAIN_CYCLES / TRAIN_CHUNKS;
 
-fn main() -> io::Result<()> {
-    let mut machine = Machine::new();
-    let mut result_chunks: Vec<HashMap<GameResult, u32>> = vec![HashMap::new()];
+const USAGE: &str = "Usage: menace [--seed <seed>] \
+                     [play | tui | serve [<address>] | versus <engine>... | inspect <position>...]";
+
+/// The number of games against an external engine.
+const VERSUS_GAMES: u32 = 100;
+const ENGINE_TIMEOUT: Duration = Duration::from_secs(1);
+
+fn main() -> anyhow::Result<()> {
+    let mut args = std::env::args().skip(1).collect::<Vec<_>>();
+    // A random seed unless one is given to reproduce a previous run.
+    let seed = if args.first().map(String::as_str) == Some("--seed") {
+        let seed = args.get(1).context(USAGE)?.parse()?;
+        args.drain(..2);
+        seed
+    } else {
+        rand::random()
+    };
+    match args.first().map(String::as_str) {
+        None | Some("play") => {
+            let mut machine = Machine::with_seed(seed);
+            train(&mut machine);
+            play(&mut machine)?;
+        }
+        Some("tui") => {
+            let mut machine = Machine::with_seed(seed);
+            run_tui(&mut machine, TRAIN_CYCLES)?;
+        }
+        Some("serve") => {
+            // Train quietly since the protocol may use stdout.
+            let mut machine = Machine::with_seed(seed);
+            eprintln!("Training with seed {seed}");
+            for _ in 0..TRAIN_CYCLES {
+                machine.play_training_match();
+            }
+            match args.get(1) {
+                Some(addr) => serve_tcp(&mut machine, addr.as_str())?,
+                None => serve(&mut machine, io::stdin().lock(), io::stdout())?,
+            }
+        }
+        Some("versus") => {
+            let engine_args = &args[1..];
+            anyhow::ensure!(!engine_args.is_empty(), "{USAGE}");
+            let mut command = std::process::Command::new(&engine_args[0]);
+            command.args(&engine_args[1..]);
+            let mut engine = Engine::spawn(command, ENGINE_TIMEOUT)?;
+            let mut machine = Machine::with_seed(seed);
+            train(&mut machine);
+            versus(&mut machine, &mut engine);
+        }
+        Some("inspect") => {
+            let positions = args[1..]
+                .iter()
+                .map(|s| s.parse::<State>())
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            let mut machine = Machine::with_seed(seed);
+            train(&mut machine);
+            for pos in positions {
+                println!("{pos}: {}", machine.get_move_scores(pos));
+            }
+        }
+        Some(_) => anyhow::bail!("{USAGE}"),
+    }
+    Ok(())
+}
+
+/// Train the machine and print statistics along the way.
+fn train(machine: &mut Machine) {
+    println!("Training with seed {}", machine.seed());
+    let mut chunk = ResultStats::new();
     for i in 1..=TRAIN_CYCLES {
-        let result = machine.play_training_match();
+        chunk.add(machine.play_training_match());
         if i % TRAIN_CHUNK_SIZE == 0 {
-            if let Some(chunk) = result_chunks.last() {
-                let get_percent = |filter: &dyn Fn(GameResult) -> bool| {
-                    chunk
-                        .iter()
-                        .filter(|&(&r, _)| filter(r))
-                        .map(|(_, &c)| c as f32)
-                        .sum::<f32>()
-                        / TRAIN_CHUNK_SIZE as f32
-                        * 100.0
-                };
-                let draws = get_percent(&|r| r == Draw);
-                let crosses = get_percent(&|r| match r {
-                    Win {
-                        winner: Crosses, ..
-                    } => true,
-                    _ => false,
-                });
-                let naughts = get_percent(&|r| match r {
-                    Win {
-                        winner: Naughts, ..
-                    } => true,
-                    _ => false,
-                });
-                let resignations = get_percent(&|r| match r {
-                    Win {
-                        reason: Resignation,
-                        ..
-                    } => true,
-                    _ => false,
-                });
-                println!(
-                    "{}: draws: {draws:.1}, wins: crosses: {crosses:.1}, naughts: {naughts:.1}, \
-                     resignations: {resignations:.1}",
-                    i / TRAIN_CHUNK_SIZE
-                );
-            }
-            result_chunks.push(HashMap::new());
+            println!("{}: {chunk}", i / TRAIN_CHUNK_SIZE);
+            chunk = ResultStats::new();
+        }
+    }
+    // The machine only stores positions where it has to select a move.
+    let non_terminal = open_positions::<State>().count();
+    println!(
+        "Trained on {} of {non_terminal} reachable positions",
+        machine.values().len()
+    );
+    println!(
+        "Average entropy of the move scores: {:.3} bits",
+        machine.average_entropy()
+    );
+}
+
+/// Play games against an engine, alternating sides, and print the results.
+fn versus(machine: &mut Machine, engine: &mut Engine) {
+    let mut score = Score::default();
+    for i in 0..VERSUS_GAMES {
+        let (result, machine_player) = if i % 2 == 0 {
+            (play_match(machine, engine), Crosses)
+        } else {
+            (play_match(engine, machine), Naughts)
+        };
+        println!("{}: {result}", i + 1);
+        match result {
+            Win { winner, .. } if winner == machine_player => score.wins += 1,
+            Win { .. } => score.losses += 1,
+            Draw { .. } => score.draws += 1,
         }
-        result_chunks
-            .last_mut()
-            .unwrap()
-            .entry(result)
-            .or_default()
-            .add_assign(1);
     }
-    println!("Trained on {} positions", machine.values().len());
-    println!("Starting a game against the machine:");
+    println!(
+        "Machine: {} wins, {} losses, {} draws",
+        score.wins, score.losses, score.draws
+    );
+}
+
+const HELP: &str = "\
+Commands:
+  <move>      Make a move, like b2.
+  undo        Take back your last move and the machine's reply.
+  hint        Show the moves suggested by the machine and by the solver.
+  scores      Show the machine's move scores for the position.
+  resign      Give up the game.
+  offer draw  Offer the machine a draw.
+  swap        Switch sides with the machine.
+  mode <mode> Set how the machine selects moves: proportional (the default), greedy,
+              softmax <temperature> or epsilon <epsilon>.
+  new         Abandon the game and start a new one.
+  help        Show this help.
+  quit        Stop playing.";
+
+/// A command in the interactive game.
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Command {
+    Play(Move),
+    Undo,
+    Hint,
+    Scores,
+    Resign,
+    OfferDraw,
+    Swap,
+    Mode(Selection),
+    New,
+    Help,
+    Quit,
+}
+
+impl FromStr for Command {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let words = s.split_whitespace().collect::<Vec<_>>();
+        Ok(match words.join(" ").as_str() {
+            "undo" => Command::Undo,
+            "hint" => Command::Hint,
+            "scores" => Command::Scores,
+            "resign" => Command::Resign,
+            "offer draw" | "draw" => Command::OfferDraw,
+            "swap" => Command::Swap,
+            "new" => Command::New,
+            "help" => Command::Help,
+            "quit" | "exit" => Command::Quit,
+            s if s.starts_with("mode ") => Command::Mode(s["mode ".len()..].parse()?),
+            s => s
+                .parse()
+                .map(Command::Play)
+                .map_err(|e| anyhow::anyhow!("{e} Type \"help\" for a list of commands."))?,
+        })
+    }
+}
+
+/// The running score of a session, seen from the human player or the machine.
+#[derive(Debug, Default)]
+struct Score {
+    wins: u32,
+    losses: u32,
+    draws: u32,
+}
+
+/// Play games against the machine on the terminal until the user quits.
+fn play(machine: &mut Machine) -> io::Result<()> {
     let stdin = io::stdin();
     let mut stdin_buf = String::new();
-    let mut pos = State::new();
-    let machine_player = Crosses;
-    let you = machine_player.opponent();
-    let mut turn = Crosses;
-    let result = loop {
-        if turn == machine_player {
-            println!("Move scores: {}", machine.get_move_scores(pos));
-            let Some(m) = machine.select_move(pos) else {
-                break Win {
-                    winner: you,
-                    reason: Resignation,
-                };
+    let colors = io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+    let mut solver = Solver::new();
+    let mut score = Score::default();
+    let mut machine_player = Crosses;
+    println!("Type \"help\" for a list of commands.");
+    'games: loop {
+        println!(
+            "Starting a game against the machine, you play {}:",
+            machine_player.opponent()
+        );
+        let mut pos = Position::<State>::new();
+        let result = loop {
+            if let Some(res) = pos.result() {
+                break res;
+            }
+            let you = machine_player.opponent();
+            if pos.turn() == machine_player {
+                match machine.select_move(*pos.pos()) {
+                    Some(m) => {
+                        println!("My move: {m}");
+                        pos.play(m)
+                            .expect("The machine should only play legal moves.");
+                    }
+                    None => pos
+                        .finish(Win {
+                            winner: you,
+                            reason: Resignation,
+                        })
+                        .unwrap(),
+                }
+                continue;
+            }
+            let view = BoardView {
+                colors,
+                last_move: pos.last_move(),
+                ..BoardView::new(pos.pos())
             };
-            println!("My move: {m}");
-            pos.play(m, machine_player);
-            println!("{pos:?}");
-        } else {
+            println!("{view}");
             print!("Your move: ");
             io::stdout().flush()?;
             stdin_buf.clear();
-            stdin.read_line(&mut stdin_buf)?;
-            let m = match stdin_buf.trim().parse::<Move>() {
-                Ok(m) => m,
+            if stdin.read_line(&mut stdin_buf)? == 0 {
+                break 'games;
+            }
+            let command = match stdin_buf.parse::<Command>() {
+                Ok(command) => command,
                 Err(e) => {
                     eprintln!("Error: {e}");
                     continue;
                 }
             };
-            if !pos.legal_moves().contains(m) {
-                eprintln!("Error: The move {m} is not a legal move in this position.");
-                continue;
+            match command {
+                Command::Play(m) => {
+                    if let Err(e) = pos.play(m) {
+                        eprintln!("Error: {e}");
+                    }
+                }
+                Command::Undo => {
+                    // Your last move is followed by the replies of the machine, which are more
+                    // than one move when you swapped sides in between.
+                    match pos.undo_by(you) {
+                        Ok(m) => println!("Took back {m}."),
+                        Err(_) => eprintln!("Error: You have not made any move to undo."),
+                    }
+                }
+                Command::Hint => {
+                    let machine_move = match machine.values().get(pos.pos()) {
+                        Some(scores) => scores.best().unwrap().to_string(),
+                        None => "a random move".to_string(),
+                    };
+                    let best_moves = solver
+                        .best_moves(*pos.pos(), you)
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>();
+                    println!(
+                        "The machine would most likely play {machine_move}, the solver \
+                         suggests {}.",
+                        best_moves.join(", ")
+                    );
+                }
+                Command::Scores => {
+                    // Looked up without inserting, so asking doesn't add a learned position.
+                    let scores = machine.values().get(pos.pos());
+                    match scores {
+                        Some(scores) => println!("Move scores: {scores}"),
+                        None => println!("The machine has not learned this position."),
+                    }
+                    println!("{}", BoardView { scores, ..view });
+                }
+                Command::Resign => pos
+                    .finish(Win {
+                        winner: machine_player,
+                        reason: Resignation,
+                    })
+                    .unwrap(),
+                Command::OfferDraw => {
+                    // The machine accepts unless the solver says that it can win.
+                    if solver.outcome(*pos.pos(), you) == Lost {
+                        println!("The machine declines the draw.");
+                    } else {
+                        println!("The machine accepts the draw.");
+                        pos.finish(Draw { reason: Agreement }).unwrap();
+                    }
+                }
+                Command::Swap => {
+                    machine_player = you;
+                    println!("You now play {}.", machine_player.opponent());
+                }
+                Command::Mode(selection) => {
+                    machine.set_selection(selection);
+                    println!("The machine now selects moves by {selection}.");
+                }
+                Command::New => continue 'games,
+                Command::Help => println!("{HELP}"),
+                Command::Quit => break 'games,
             }
-            pos.play(m, you);
-        }
-        if let Some(res) = pos.result(turn) {
-            break res;
-        }
-        turn = turn.opponent();
-    };
-    println!("{result:?}");
-    if let Win { winner, .. } = result {
-        if winner == machine_player {
-            println!("Haha! You lost!");
-        } else {
-            println!("The machine is bad, so you won!");
+        };
+        let view = BoardView {
+            colors,
+            last_move: pos.last_move(),
+            ..BoardView::new(pos.pos())
+        };
+        println!("{view}");
+        println!("{result}.");
+        match result {
+            Win { winner, .. } if winner == machine_player => {
+                println!("Haha! You lost!");
+                score.losses += 1;
+            }
+            Win { .. } => {
+                println!("The machine is bad, so you won!");
+                score.wins += 1;
+            }
+            Draw { .. } => score.draws += 1,
         }
+        println!(
+            "Score: {} wins, {} losses and {} draws.",
+            score.wins, score.losses, score.draws
+        );
     }
     Ok(())
 }
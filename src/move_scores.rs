@@ -1,143 +1,508 @@
-use crate::tic_tac_toe::*;
-use crate::Move;
+use crate::*;
 use getset::{CopyGetters, Getters};
+use rand::seq::SliceRandom as _;
+use rand::Rng;
 use std::fmt;
 
+/// Totals outside of this range are rescaled to 1.0, so weights neither overflow nor underflow.
+const MIN_TOTAL: f64 = 1e-100;
+const MAX_TOTAL: f64 = 1e100;
+
+/// The results of the games in which a move was played.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, CopyGetters)]
+#[getset(get_copy = "pub")]
+pub struct MoveStats {
+    wins: u64,
+    draws: u64,
+    losses: u64,
+}
+
+impl MoveStats {
+    /// The number of games in which the move was played.
+    pub fn visits(&self) -> u64 {
+        self.wins + self.draws + self.losses
+    }
+}
+
 /// Scores for moves in a position.
+///
+/// The scores are stored as weights relative to their total in a sum tree, so sampling a move
+/// and updating a score takes O(log N) time.
 #[derive(Debug, Getters, CopyGetters)]
-pub struct MoveScores {
-    /// score[m] = the score for move m:
-    ///
-    /// The sum of all scores should be 1.0.
-    #[getset(get = "pub")]
-    score: [f64; Move::N],
-    /// order[m] = i implies that m is the move with the ith highest score:
-    #[getset(get = "pub")]
-    order: [usize; Move::N],
-    /// move_at[i] = m implies that m is the move with the ith highest score:
-    #[getset(get = "pub")]
-    move_at: [Move; Move::N],
+pub struct MoveScores<M: GameMove = Move> {
+    /// A complete binary tree where tree[1] is the root and tree[i] is the sum of tree[2 * i]
+    /// and tree[2 * i + 1]. The leaves tree[leaves + m] are the weights of the moves m, so the
+    /// score of m is tree[leaves + m] / tree[1].
+    tree: Vec<f64>,
+    /// A tree like `tree` where the inner nodes are the minimum instead of the sum, and the
+    /// leaves of illegal moves are infinite, so the lowest score is known at any time.
+    min_tree: Vec<f64>,
+    /// The index of the first leaf, the number of moves rounded up to a power of two.
+    leaves: usize,
     /// If all moves are 0.
     #[getset(get_copy = "pub")]
     all_zero: bool,
     /// The number of times a score has been adjusted.
     #[getset(get_copy = "pub")]
     adjusted: u64,
+    /// The number of games in which a move was played in the position.
+    #[getset(get_copy = "pub")]
+    visits: u64,
+    /// stats[m] = the results of the games in which m was played:
+    stats: Vec<MoveStats>,
+    marker: std::marker::PhantomData<M>,
 }
 
-impl MoveScores {
-    /// Initialize all legal moves to `initial_score` and the other to 0.
-    pub fn initial(pos: &State) -> Self {
+impl<M: GameMove> MoveScores<M> {
+    /// Give all legal moves the same score, 1 / n for n legal moves, and the other moves 0. All
+    /// scores are 0 if there is no legal move.
+    pub fn initial<G: Game<Move = M>>(pos: &G) -> Self {
+        let leaves = M::N.next_power_of_two();
         let mut res = Self {
-            score: [0.0; Move::N],
-            order: Default::default(),
-            move_at: Default::default(),
+            tree: vec![0.0; 2 * leaves],
+            min_tree: vec![f64::INFINITY; 2 * leaves],
+            leaves,
             all_zero: true,
             adjusted: 1,
+            visits: 0,
+            stats: vec![MoveStats::default(); M::N],
+            marker: std::marker::PhantomData,
         };
-        // Fill order and move_at with an initial order:
-        for i in 0..Move::N {
-            res.order[i] = i;
-            res.move_at[i] = Move::from_usize(i);
-        }
-        // Fill in legal moves:
-        let moves = pos.legal_moves();
-        if moves.size() > 0 {
+        for m in pos.legal_moves() {
+            res.tree[leaves + m.to_usize()] = 1.0;
+            res.min_tree[leaves + m.to_usize()] = 1.0;
             res.all_zero = false;
         }
-        for (i, m) in moves.iter().enumerate() {
-            let m_i = m.to_usize();
-            res.score[m_i] = 1.0 / moves.size() as f64;
-            assert!(i <= m_i);
-            let m2 = res.move_at[i];
-            res.move_at[m_i] = m2;
-            res.order[m2.to_usize()] = m_i;
-            res.move_at[i] = m;
-            res.order[m_i] = i;
-        }
-        res.validate();
+        res.rebuild();
         res
     }
 
-    /// Validate that all the internal arrays are correct.
+    /// The weight of a move.
+    fn weight(&self, m: M) -> f64 {
+        self.tree[self.leaves + m.to_usize()]
+    }
+
+    /// The sum of all weights.
+    fn total(&self) -> f64 {
+        self.tree[1]
+    }
+
+    /// Recompute an inner node of the trees from its children.
+    fn update_node(&mut self, i: usize) {
+        self.tree[i] = self.tree[2 * i] + self.tree[2 * i + 1];
+        self.min_tree[i] = self.min_tree[2 * i].min(self.min_tree[2 * i + 1]);
+    }
+
+    /// Recompute all inner nodes of the trees from the leaves.
+    fn rebuild(&mut self) {
+        for i in (1..self.leaves).rev() {
+            self.update_node(i);
+        }
+    }
+
+    /// Set the weight of a legal move and update the trees.
+    fn set_weight(&mut self, m: M, weight: f64) {
+        let mut i = self.leaves + m.to_usize();
+        self.tree[i] = weight;
+        self.min_tree[i] = weight;
+        while i > 1 {
+            i /= 2;
+            self.update_node(i);
+        }
+    }
+
+    /// Raise the scores below `min_score` to `min_score`, scaling down the other scores.
+    fn raise_to(&mut self, min_score: f64) {
+        let legal_weights = || {
+            (self.leaves..self.leaves + M::N)
+                .filter(|&i| self.min_tree[i].is_finite())
+                .map(|i| self.tree[i])
+        };
+        // The low moves get min_score * total and the others keep their weights. Raising the low
+        // scores lowers the others, so the total only grows and more scores may become low.
+        let mut total = self.total();
+        let mut low_count = 0;
+        loop {
+            let threshold = min_score * total;
+            let new_low_count = legal_weights().filter(|&w| w < threshold).count();
+            if new_low_count <= low_count {
+                break;
+            }
+            low_count = new_low_count;
+            let high_sum = legal_weights().filter(|&w| w >= threshold).sum::<f64>();
+            total = high_sum / (1.0 - low_count as f64 * min_score);
+        }
+        let threshold = min_score * total;
+        for i in self.leaves..self.leaves + M::N {
+            if self.min_tree[i] < threshold {
+                self.tree[i] = threshold;
+                self.min_tree[i] = threshold;
+            }
+        }
+        self.rebuild();
+    }
+
+    /// The results of the games in which a move was played.
+    pub fn move_stats(&self, m: M) -> MoveStats {
+        self.stats[m.to_usize()]
+    }
+
+    /// Record the result of a game in which `player` played `m` in the position.
+    pub fn record(&mut self, m: M, player: Player, result: GameResult) {
+        let stats = &mut self.stats[m.to_usize()];
+        match result {
+            Draw { .. } => stats.draws += 1,
+            Win { winner, .. } if winner == player => stats.wins += 1,
+            Win { .. } => stats.losses += 1,
+        }
+        self.visits += 1;
+    }
+
+    /// The score of a move, the probability that it is selected.
+    ///
+    /// The sum of all scores is 1.0 unless all moves are 0.
+    pub fn score(&self, m: M) -> f64 {
+        if self.all_zero {
+            0.0
+        } else {
+            self.weight(m) / self.total()
+        }
+    }
+
+    /// The Shannon entropy of the scores in bits, 0.0 if the machine always makes the same move
+    /// and log2(n) if all n legal moves have the same score.
+    pub fn entropy(&self) -> f64 {
+        (0..M::N)
+            .map(|i| self.score(M::from_usize(i)))
+            .filter(|&p| p > 0.0)
+            .map(|p| -p * p.log2())
+            .sum()
+    }
+
+    /// The Kullback-Leibler divergence in bits of `other` from these scores, which is 0.0 if the
+    /// scores are equal. Infinite if `other` has a score of 0 for a move with a score above 0.
+    pub fn kl_divergence(&self, other: &Self) -> f64 {
+        (0..M::N)
+            .map(M::from_usize)
+            .map(|m| (self.score(m), other.score(m)))
+            .filter(|&(p, _)| p > 0.0)
+            .map(|(p, q)| p * (p / q).log2())
+            .sum()
+    }
+
+    /// All moves with a score above 0, ordered from the highest to the lowest score.
+    pub fn ranked(&self) -> Vec<M> {
+        let mut moves = (0..M::N)
+            .map(M::from_usize)
+            .filter(|&m| self.weight(m) > 0.0)
+            .collect::<Vec<_>>();
+        moves.sort_by(|&a, &b| self.weight(b).total_cmp(&self.weight(a)));
+        moves
+    }
+
+    /// The move with the highest score, if any.
+    pub fn best(&self) -> Option<M> {
+        (0..M::N)
+            .map(M::from_usize)
+            .filter(|&m| self.weight(m) > 0.0)
+            .max_by(|&a, &b| self.weight(a).total_cmp(&self.weight(b)))
+    }
+
+    /// Select a move with probability equal to its score, or `None` if all moves are 0.
+    pub fn sample(&self, rng: &mut impl Rng) -> Option<M> {
+        if self.all_zero {
+            return None;
+        }
+        let mut x = rng.gen::<f64>() * self.total();
+        let mut i = 1;
+        while i < self.leaves {
+            // Go to the left child, or to the right child if x is past the left subtree.
+            i *= 2;
+            if x >= self.tree[i] {
+                x -= self.tree[i];
+                i += 1;
+            }
+        }
+        let m = i - self.leaves;
+        if m < M::N && self.tree[i] > 0.0 {
+            Some(M::from_usize(m))
+        } else {
+            // Rounding errors may lead just past the last move with a weight.
+            self.best()
+        }
+    }
+
+    /// Select a move according to `selection`, or `None` if all moves are 0.
+    pub fn select(&self, selection: Selection, rng: &mut impl Rng) -> Option<M> {
+        match selection {
+            Selection::Proportional => self.sample(rng),
+            Selection::Greedy => self.best(),
+            Selection::EpsilonGreedy { epsilon } if rng.gen::<f64>() < epsilon => {
+                self.ranked().choose(rng).copied()
+            }
+            Selection::EpsilonGreedy { .. } => self.best(),
+            // Greedy selection is the limit for temperatures going to 0.
+            Selection::Softmax { temperature } if temperature <= 0.0 => self.best(),
+            Selection::Softmax { temperature } => {
+                // Work with logarithms relative to the best move, so powers don't underflow.
+                let moves = self.ranked();
+                let max_log = self.weight(*moves.first()?).ln();
+                let weights = moves
+                    .iter()
+                    .map(|&m| ((self.weight(m).ln() - max_log) / temperature).exp())
+                    .collect::<Vec<_>>();
+                let mut x = rng.gen::<f64>() * weights.iter().sum::<f64>();
+                for (&m, w) in moves.iter().zip(weights) {
+                    if x < w {
+                        return Some(m);
+                    }
+                    x -= w;
+                }
+                moves.last().copied()
+            }
+        }
+    }
+
+    /// Validate that the tree is consistent.
     fn validate(&self) {
         if self.all_zero() {
             return;
         }
-        let mut score_sum = 0.0;
-        let mut prev_score = f64::INFINITY;
-        for i in 0..Move::N {
-            let m = self.move_at[i];
-            let m_i = m.to_usize();
-            assert_eq!(self.order[m_i], i);
-            assert!(prev_score >= self.score[m_i]);
-            score_sum += self.score[m_i];
-            prev_score = self.score[m_i];
+        for i in 1..self.leaves {
+            assert_eq!(self.tree[i], self.tree[2 * i] + self.tree[2 * i + 1]);
+            assert_eq!(
+                self.min_tree[i],
+                self.min_tree[2 * i].min(self.min_tree[2 * i + 1])
+            );
         }
-        let epsilon = f64::EPSILON * 1e4;
-        assert!(1.0 - epsilon < score_sum && score_sum < 1.0 + epsilon);
+        assert!(self.tree[self.leaves + M::N..].iter().all(|&w| w == 0.0));
+        assert!(self.tree[self.leaves..].iter().all(|&w| w >= 0.0));
+        assert!(self.total() > 0.0);
     }
 
     /// Multiply the score for a move relative the other scores. The sum of the scores will still
-    /// be 1.0.
-    pub fn multiply(&mut self, m: Move, factor: f64) -> f64 {
-        assert!(factor.is_finite());
-        let m_i = m.to_usize();
-        assert!(0.0 < self.score[m_i]);
-        self.score[m_i] *= factor;
-        let total_factor = 1.0 / self.score.iter().sum::<f64>();
-        self.score.iter_mut().for_each(|s| *s *= total_factor);
+    /// be 1.0. Scores which end up below `min_score` are raised to it. Returns the factor which
+    /// the other scores were multiplied with.
+    ///
+    /// With a `min_score` above 0, every legal move keeps a score of at least `min_score` and at
+    /// most `1.0 - (n - 1) * min_score` for n legal moves, so no score underflows to 0. That
+    /// needs `n * min_score < 1.0`. `min_score` must be below `1.0 / M::N` rather than `1.0 / n`,
+    /// since the same `min_score` is used for all positions and at most `M::N` moves are legal in
+    /// any of them.
+    pub fn multiply(&mut self, m: M, factor: f64, min_score: f64) -> f64 {
+        assert!(factor.is_finite() && factor > 0.0);
+        assert!((0.0..1.0 / M::N as f64).contains(&min_score));
+        assert!(
+            0.0 < self.weight(m),
+            "The score of {m} has underflowed to 0."
+        );
+        let old_total = self.total();
+        let old_weight = self.weight(m);
+        // Bound the weight so that the total stays finite and above 0 for any factor.
+        let weight = (old_weight * factor).clamp(f64::MIN_POSITIVE, old_total * MAX_TOTAL);
+        self.set_weight(m, weight);
+        if self.min_tree[1] < min_score * self.total() {
+            self.raise_to(min_score);
+        }
         self.adjusted += 1;
-        let mut i = self.order[m_i];
-        if factor > 1.0 {
-            while i > 0 {
-                let prev_m = self.move_at[i - 1];
-                let prev_m_i = prev_m.to_usize();
-                if self.score[prev_m_i] >= self.score[m_i] {
-                    break;
-                }
-                self.order[prev_m_i] += 1;
-                self.order[m_i] -= 1;
-                self.move_at[i] = prev_m;
-                self.move_at[i - 1] = m;
-                i -= 1;
-            }
-        } else {
-            while i + 1 < Move::N {
-                let next_m = self.move_at[i + 1];
-                let next_m_i = next_m.to_usize();
-                if self.score[next_m_i] <= self.score[m_i] {
-                    break;
-                }
-                self.order[next_m_i] -= 1;
-                self.order[m_i] += 1;
-                self.move_at[i] = next_m;
-                self.move_at[i + 1] = m;
-                i += 1;
+        let total_factor = old_total / self.total();
+        if !(MIN_TOTAL..=MAX_TOTAL).contains(&self.total()) {
+            let scale = 1.0 / self.total();
+            for i in self.leaves..2 * self.leaves {
+                self.tree[i] *= scale;
+                self.min_tree[i] *= scale;
             }
+            self.rebuild();
+        }
+        if cfg!(debug_assertions) {
+            self.validate();
         }
-        self.validate();
         total_factor
     }
 }
 
-impl fmt::Display for MoveScores {
+/// Shows every move with a score above 0 as "move: score (wins/draws/losses)".
+impl<M: GameMove> fmt::Display for MoveScores<M> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "adjusted {}s, ", self.adjusted)?;
+        write!(f, "adjusted {}s, visited {}s, ", self.adjusted, self.visits)?;
         let mut numfmtr = numfmt::Formatter::new().precision(numfmt::Precision::Significance(3));
-        for (i, m) in self.move_at().iter().enumerate() {
+        let ranked = self.ranked();
+        if ranked.is_empty() {
+            write!(f, "EMPTY")?;
+        }
+        for (i, &m) in ranked.iter().enumerate() {
             if i > 0 {
                 write!(f, ", ")?;
             }
-            let score = self.score()[m.to_usize()];
-            if score == 0.0 {
-                if i == 0 {
-                    write!(f, "EMPTY")?;
-                }
-                break;
-            }
-            write!(f, "{m}: {}", numfmtr.fmt2(score))?;
+            let stats = self.move_stats(m);
+            write!(
+                f,
+                "{m}: {} ({}/{}/{})",
+                numfmtr.fmt2(self.score(m)),
+                stats.wins,
+                stats.draws,
+                stats.losses
+            )?;
         }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::prelude::*;
+
+    #[test]
+    fn test_multiply_and_sample() {
+        let pos = "X../.O./... x".parse::<State>().unwrap();
+        let mut scores = MoveScores::initial(&pos);
+        let [a2, b1, c3] = ["a2", "b1", "c3"].map(|m| m.parse::<Move>().unwrap());
+        assert_eq!(scores.score(a2), 1.0 / 7.0);
+        assert_eq!(scores.score("a1".parse().unwrap()), 0.0);
+
+        // The other scores are scaled by 1 / (1 - 1/7 + 4/7).
+        let total_factor = scores.multiply(a2, 4.0, 0.0);
+        assert!((total_factor - 7.0 / 10.0).abs() < 1e-12);
+        assert!((scores.score(a2) - 0.4).abs() < 1e-12);
+        scores.multiply(c3, 0.5, 0.0);
+        assert_eq!(scores.ranked()[0], a2);
+        assert_eq!(*scores.ranked().last().unwrap(), c3);
+        assert_eq!(scores.best(), Some(a2));
+        let sum = Move::all().map(|m| scores.score(m)).sum::<f64>();
+        assert!((sum - 1.0).abs() < 1e-12);
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut counts = [0; Move::N];
+        for _ in 0..10000 {
+            counts[scores.sample(&mut rng).unwrap().to_usize()] += 1;
+        }
+        assert_eq!(counts[0] + counts[4], 0);
+        assert!((3500..4500).contains(&counts[a2.to_usize()]));
+        assert!(counts[b1.to_usize()] > counts[c3.to_usize()]);
+
+        // Large factors are rescaled instead of overflowing.
+        for _ in 0..1000 {
+            scores.multiply(b1, 1e10, 0.0);
+        }
+        assert!((scores.score(b1) - 1.0).abs() < 1e-12);
+        assert_eq!(scores.sample(&mut rng), Some(b1));
+    }
+
+    /// Adversarial updates: mostly punish the moves the scores select, and sometimes reward a
+    /// random move a lot.
+    fn stress<G: Game>(updates: u32) {
+        let mut scores = MoveScores::initial(&G::new());
+        let mut rng = StdRng::seed_from_u64(0);
+        let min_score = 1e-6;
+        let factors = [32.0, 1.0 / 32.0, 0.9, 1e10, 1e-10, 1e300, 1e-300];
+        for i in 0..updates {
+            let m = if i % 3 == 0 {
+                G::Move::from_usize(rng.gen_range(0..G::Move::N))
+            } else {
+                scores.sample(&mut rng).unwrap()
+            };
+            let factor = factors[rng.gen_range(0..factors.len())];
+            let total_factor = scores.multiply(m, factor, min_score);
+            assert!(total_factor.is_finite() && total_factor > 0.0);
+        }
+        let mut sum = 0.0;
+        for m in (0..G::Move::N).map(G::Move::from_usize) {
+            let score = scores.score(m);
+            assert!(score.is_finite());
+            assert!(score >= min_score * (1.0 - 1e-9), "{m}: {score}");
+            sum += score;
+        }
+        assert!((sum - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_min_score() {
+        stress::<State>(1_000_000);
+        stress::<UltimateState>(50_000);
+
+        // The ceiling follows from the floor.
+        let pos = State::new();
+        let mut scores = MoveScores::initial(&pos);
+        let m = Move::from_usize(4);
+        for _ in 0..1000 {
+            scores.multiply(m, 1e10, 0.01);
+        }
+        assert!((scores.score(m) - 0.92).abs() < 1e-9);
+        assert!((scores.score(Move::from_usize(0)) - 0.01).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_select() {
+        let pos = "X../.O./... x".parse::<State>().unwrap();
+        let mut scores = MoveScores::initial(&pos);
+        let [a2, c3] = ["a2", "c3"].map(|m| m.parse::<Move>().unwrap());
+        scores.multiply(a2, 4.0, 0.0);
+        scores.multiply(c3, 0.25, 0.0);
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut count = |selection, m: Move| {
+            (0..10000)
+                .filter(|_| scores.select(selection, &mut rng) == Some(m))
+                .count()
+        };
+        assert_eq!(count(Selection::Greedy, a2), 10000);
+        assert_eq!(count(Selection::EpsilonGreedy { epsilon: 0.0 }, a2), 10000);
+        // a2 is selected as the best move or as one of 7 random moves.
+        let epsilon = count(Selection::EpsilonGreedy { epsilon: 0.5 }, a2);
+        assert!((5400..6000).contains(&epsilon), "{epsilon}");
+        // a2 has a score of 4 / 9.25 and the other moves of 1 / 9.25, except 0.25 / 9.25 for c3.
+        let proportional = count(Selection::Proportional, a2);
+        assert!((4100..4550).contains(&proportional), "{proportional}");
+        let cold = count(Selection::Softmax { temperature: 0.2 }, a2);
+        assert!(cold > 9900, "{cold}");
+        assert_eq!(count(Selection::Softmax { temperature: 0.0 }, a2), 10000);
+        let hot = count(Selection::Softmax { temperature: 100.0 }, c3);
+        assert!((1300..1500).contains(&hot), "{hot}");
+        assert_eq!(
+            MoveScores::initial(&"XOX/XOO/OXX".parse::<State>().unwrap())
+                .select(Selection::Softmax { temperature: 1.0 }, &mut rng),
+            None
+        );
+    }
+
+    #[test]
+    fn test_stats() {
+        let pos = "XOX/OOX/X.. o".parse::<State>().unwrap();
+        let mut scores = MoveScores::initial(&pos);
+        let [c2, c3] = ["c2", "c3"].map(|m| m.parse::<Move>().unwrap());
+        let win = Win {
+            winner: Crosses,
+            reason: RowOrColumn,
+        };
+        scores.record(c2, Crosses, win);
+        scores.record(c2, Crosses, Draw { reason: BoardFull });
+        scores.record(c3, Naughts, win);
+        scores.multiply(c2, 2.0, 0.0);
+        assert_eq!(scores.visits(), 3);
+        assert_eq!(scores.move_stats(c2).visits(), 2);
+        assert_eq!(scores.move_stats(c3).losses(), 1);
+        assert_eq!(
+            scores.to_string(),
+            "adjusted 2s, visited 3s, c2: 0.66 (1/1/0), c3: 0.33 (0/0/1)"
+        );
+    }
+
+    #[test]
+    fn test_entropy() {
+        let pos = "X../.O./... x".parse::<State>().unwrap();
+        let uniform = MoveScores::initial(&pos);
+        assert!((uniform.entropy() - 7f64.log2()).abs() < 1e-12);
+        assert_eq!(uniform.kl_divergence(&uniform), 0.0);
+
+        // Scores of 1/2 for a2 and 1/12 for the other 6 moves.
+        let mut scores = MoveScores::initial(&pos);
+        scores.multiply("a2".parse().unwrap(), 6.0, 0.0);
+        let expected = 0.5 + 0.5 * 12f64.log2();
+        assert!((scores.entropy() - expected).abs() < 1e-12);
+        let expected = 0.5 * (3.5f64).log2() + 0.5 * (7.0 / 12.0f64).log2();
+        assert!((scores.kl_divergence(&uniform) - expected).abs() < 1e-12);
+
+        let other = MoveScores::initial(&"X../.O./X.. o".parse::<State>().unwrap());
+        assert_eq!(uniform.kl_divergence(&other), f64::INFINITY);
+    }
+}
@@ -17,7 +17,7 @@ impl Move {
 
     /// Get a list of all moves.  Garanteed to be ordered by `Move::to_usize()`.
     pub fn all() -> impl Iterator<Item = Self> {
-        (0..Self::N).map(|i| Move(i))
+        (0..Self::N).map(Move)
     }
 
     pub const fn to_usize(&self) -> usize {
@@ -51,30 +51,37 @@ impl fmt::Display for Move {
 impl FromStr for Move {
     type Err = anyhow::Error;
     fn from_str(s: &str) -> anyhow::Result<Self> {
-        let mut chars = s.chars();
-        let letter = chars.next().context("A move cannot be an empty string.")?;
-        let letter_idx = letter as u32;
-        let a_idx = 'a' as u32;
-        anyhow::ensure!(
-            a_idx <= letter_idx && letter_idx < a_idx + 3,
-            "The letter in a move must be between a and c."
-        );
-        let row = letter_idx - a_idx;
-        let col = chars
-            .as_str()
-            .parse::<usize>()
-            .context("The move column should be represented by a number.")?;
-        anyhow::ensure!(
-            1 <= col && col < 4,
-            "The move column must be in the range [1, 3]."
-        );
-        let col = col - 1;
-        let m_i = row as usize * 3 + col;
+        let (row, col) = parse_square(s, 3)?;
+        let m_i = row * 3 + col;
         assert!(m_i < Move::N);
         Ok(Move::from_usize(m_i))
     }
 }
 
+/// Parse a square like "b3" on a board with `size` rows and columns and return the zero based
+/// row and column.
+pub(crate) fn parse_square(s: &str, size: usize) -> anyhow::Result<(usize, usize)> {
+    let mut chars = s.chars();
+    let letter = chars.next().context("A move cannot be an empty string.")?;
+    let letter_idx = letter as u32;
+    let a_idx = 'a' as u32;
+    let last_letter = char::from_u32(a_idx + size as u32 - 1).unwrap();
+    anyhow::ensure!(
+        (a_idx..a_idx + size as u32).contains(&letter_idx),
+        "The letter in a move must be between a and {last_letter}."
+    );
+    let row = letter_idx - a_idx;
+    let col = chars
+        .as_str()
+        .parse::<usize>()
+        .context("The move column should be represented by a number.")?;
+    anyhow::ensure!(
+        (1..=size).contains(&col),
+        "The move column must be in the range [1, {size}]."
+    );
+    Ok((row as usize, col - 1))
+}
+
 impl MoveSet {
     pub const fn empty() -> Self {
         MoveSet(0)
@@ -135,7 +142,7 @@ mod tests {
         // Test empty and all:
         assert_eq!(MoveSet::empty().size(), 0);
         assert_eq!(MoveSet::all().size(), Move::N);
-        let mut mset = MoveSet::all();
+        let mset = MoveSet::all();
         for m in (0..Move::N).map(Move::from_usize) {
             assert!(mset.contains(m));
         }
@@ -0,0 +1,203 @@
+use crate::tic_tac_toe::LINES;
+use crate::*;
+use anyhow::Context as _;
+use std::fmt;
+use std::str::FromStr;
+
+/// The sum of a winning line.
+const TARGET: u32 = 15;
+
+/// A move in numerical tic-tac-toe: a cell and the number to write there.
+///
+/// Written as the cell followed by the number, like "b2=5".
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct NumericalMove {
+    pub cell: Move,
+    /// A number in the range [1, 9].
+    pub number: u8,
+}
+
+/// A state in numerical tic-tac-toe.
+///
+/// The players take turns writing the numbers 1 to 9 on a tic-tac-toe board, each number at most
+/// once. Unlike ordinary tic-tac-toe the players do not own marks: the first player (`Crosses`)
+/// writes the odd numbers and the second player (`Naughts`) the even numbers. Whoever completes
+/// a line of three numbers summing to 15 wins, regardless of who wrote the other numbers.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub struct NumericalState {
+    /// cells[m] = the number in cell m, or 0 if the cell is empty:
+    cells: [u8; Move::N],
+}
+
+impl NumericalMove {
+    pub const N: usize = Move::N * 9;
+}
+
+impl GameMove for NumericalMove {
+    const N: usize = NumericalMove::N;
+
+    fn to_usize(self) -> usize {
+        self.cell.to_usize() * 9 + self.number as usize - 1
+    }
+
+    fn from_usize(i: usize) -> Self {
+        Self {
+            cell: Move::from_usize(i / 9),
+            number: (i % 9 + 1) as u8,
+        }
+    }
+}
+
+impl fmt::Display for NumericalMove {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}={}", self.cell, self.number)
+    }
+}
+
+impl FromStr for NumericalMove {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let (cell, number) = s
+            .split_once('=')
+            .context("A numerical move should be a cell and a number, like b2=5.")?;
+        let number = number
+            .parse::<u8>()
+            .context("The number in a move should be a number.")?;
+        anyhow::ensure!(
+            (1..=9).contains(&number),
+            "The number in a move must be in the range [1, 9]."
+        );
+        Ok(Self {
+            cell: cell.parse()?,
+            number,
+        })
+    }
+}
+
+impl NumericalState {
+    /// An empty board.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the number in a cell, or `None` if it is empty.
+    pub fn get(&self, m: Move) -> Option<u8> {
+        Some(self.cells[m.to_usize()]).filter(|&n| n != 0)
+    }
+
+    /// The player to move. Crosses moves first.
+    pub fn turn(&self) -> Player {
+        if self.ply().is_multiple_of(2) {
+            Crosses
+        } else {
+            Naughts
+        }
+    }
+
+    /// The numbers a player may write: odd numbers for crosses and even numbers for naughts.
+    pub fn numbers(player: Player) -> impl Iterator<Item = u8> {
+        let first = match player {
+            Crosses => 1,
+            Naughts => 2,
+        };
+        (first..=9).step_by(2)
+    }
+
+    /// Check if a number has been written on the board.
+    pub fn is_used(&self, number: u8) -> bool {
+        self.cells.contains(&number)
+    }
+
+    /// All legal moves for the player to move.  Garanteed to be ordered by
+    /// `GameMove::to_usize()`.
+    pub fn legal_moves(&self) -> impl Iterator<Item = NumericalMove> + '_ {
+        Move::all()
+            .filter(|&cell| self.get(cell).is_none())
+            .flat_map(|cell| {
+                Self::numbers(self.turn())
+                    .filter(|&number| !self.is_used(number))
+                    .map(move |number| NumericalMove { cell, number })
+            })
+    }
+
+    /// Write a number on the board.
+    pub fn play(&mut self, m: NumericalMove, player: Player) {
+        debug_assert_eq!(self.turn(), player);
+        debug_assert_eq!(self.get(m.cell), None);
+        debug_assert!(!self.is_used(m.number));
+        debug_assert_eq!(m.number % 2 == 1, player == Crosses);
+        self.cells[m.cell.to_usize()] = m.number;
+    }
+
+    /// Given the player who made the last move, return the result if the game is over.
+    pub fn result(&self, player: Player) -> Option<GameResult> {
+        let winning_line = LINES.iter().find(|(line, _)| {
+            line.iter().all(|&c| self.cells[c] != 0)
+                && line.iter().map(|&c| self.cells[c] as u32).sum::<u32>() == TARGET
+        });
+        match winning_line {
+            Some(&(_, diagonal)) => Some(Win {
+                winner: player,
+                reason: if diagonal { Diagonal } else { RowOrColumn },
+            }),
+            None if self.ply() == Move::N => Some(Draw { reason: BoardFull }),
+            None => None,
+        }
+    }
+}
+
+impl Game for NumericalState {
+    type Move = NumericalMove;
+
+    fn new() -> Self {
+        NumericalState::new()
+    }
+
+    fn ply(&self) -> usize {
+        self.cells.iter().filter(|&&n| n != 0).count()
+    }
+
+    fn legal_moves(&self) -> impl Iterator<Item = NumericalMove> {
+        NumericalState::legal_moves(self)
+    }
+
+    fn play(&mut self, m: NumericalMove, player: Player) {
+        NumericalState::play(self, m, player)
+    }
+
+    fn result(&self, player: Player) -> Option<GameResult> {
+        NumericalState::result(self, player)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_numerical() {
+        let mut pos = NumericalState::new();
+        assert_eq!(pos.legal_moves().count(), 9 * 5);
+        assert!(pos.legal_moves().all(|m| m.number % 2 == 1));
+        for (m, player) in [("a1=9", Crosses), ("b1=2", Naughts), ("b2=5", Crosses)] {
+            let m = m.parse().unwrap();
+            assert!(pos.legal_moves().any(|legal| legal == m));
+            pos.play(m, player);
+            assert_eq!(pos.result(player), None);
+        }
+        assert_eq!(pos.turn(), Naughts);
+        assert!(pos
+            .legal_moves()
+            .all(|m| m.number % 2 == 0 && m.number != 2));
+        // Naughts can not complete the diagonal with 9 + 5 + 1, but 9 + 2 + 4 completes the first
+        // column.
+        pos.play("c1=4".parse().unwrap(), Naughts);
+        assert_eq!(
+            pos.result(Naughts),
+            Some(Win {
+                winner: Naughts,
+                reason: RowOrColumn
+            })
+        );
+    }
+}
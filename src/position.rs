@@ -0,0 +1,165 @@
+use crate::*;
+use anyhow::Context as _;
+use getset::{CopyGetters, Getters};
+
+/// A position in a game together with the moves leading to it.
+///
+/// Unlike `Game::play`, moves are checked to be legal and errors are returned instead of
+/// panicking.
+#[derive(Debug, Clone, Getters, CopyGetters)]
+pub struct Position<G: Game = State> {
+    /// The current position.
+    #[getset(get = "pub")]
+    pos: G,
+    /// The player to move.
+    #[getset(get_copy = "pub")]
+    turn: Player,
+    /// The positions before every move together with the moves, oldest first.
+    #[getset(get = "pub")]
+    history: Vec<(G, G::Move)>,
+    /// The result if the game is over.
+    #[getset(get_copy = "pub")]
+    result: Option<GameResult>,
+}
+
+impl<G: Game> Default for Position<G> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<State> for Position<State> {
+    /// Start from a tic-tac-toe position, deriving the side to move from the marks.
+    fn from(pos: State) -> Self {
+        let turn = pos.side_to_move();
+        Self {
+            pos,
+            turn,
+            history: Vec::new(),
+            result: pos.result(turn.opponent()),
+        }
+    }
+}
+
+impl<G: Game> Position<G> {
+    /// The initial position of the game.
+    pub fn new() -> Self {
+        Self {
+            pos: G::new(),
+            turn: Crosses,
+            history: Vec::new(),
+            result: None,
+        }
+    }
+
+    /// The last move, if any.
+    pub fn last_move(&self) -> Option<G::Move> {
+        self.history.last().map(|&(_, m)| m)
+    }
+
+    /// Check if a move is legal.
+    pub fn is_legal(&self, m: G::Move) -> bool {
+        self.result.is_none() && self.pos.legal_moves().any(|legal| legal == m)
+    }
+
+    /// Let the player to move make a move and return the result if the game is over.
+    pub fn play(&mut self, m: G::Move) -> anyhow::Result<Option<GameResult>> {
+        anyhow::ensure!(self.result.is_none(), "The game is already over.");
+        anyhow::ensure!(
+            self.is_legal(m),
+            "The move {m} is not a legal move in this position."
+        );
+        self.history.push((self.pos, m));
+        self.pos.play(m, self.turn);
+        self.result = self.pos.result(self.turn);
+        self.turn = self.turn.opponent();
+        Ok(self.result)
+    }
+
+    /// End the game with a result which is not decided on the board, like a resignation.
+    pub fn finish(&mut self, result: GameResult) -> anyhow::Result<()> {
+        anyhow::ensure!(self.result.is_none(), "The game is already over.");
+        self.result = Some(result);
+        Ok(())
+    }
+
+    /// Take back the last move and return it.
+    pub fn undo(&mut self) -> anyhow::Result<G::Move> {
+        let (pos, m) = self.history.pop().context("There is no move to undo.")?;
+        self.pos = pos;
+        self.turn = self.turn.opponent();
+        self.result = None;
+        Ok(m)
+    }
+
+    /// Take back moves until a move by `player` has been taken back and return that move, like
+    /// the last move of a human player together with the replies of the machine.
+    pub fn undo_by(&mut self, player: Player) -> anyhow::Result<G::Move> {
+        // The last move was made by the opponent of the player to move.
+        let needed = if self.turn.opponent() == player { 1 } else { 2 };
+        anyhow::ensure!(
+            self.history.len() >= needed,
+            "There is no move by {player} to undo."
+        );
+        loop {
+            let m = self.undo()?;
+            if self.turn == player {
+                return Ok(m);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_play_and_undo() {
+        let mut pos = Position::<State>::new();
+        let moves = ["a1", "b1", "a2", "b2"].map(|m| m.parse::<Move>().unwrap());
+        for m in moves {
+            assert_eq!(pos.play(m).unwrap(), None);
+        }
+        assert_eq!(pos.turn(), Crosses);
+        assert!(pos.play(moves[0]).is_err());
+        assert_eq!(pos.history().len(), 4);
+
+        let win = "a3".parse().unwrap();
+        let result = Some(Win {
+            winner: Crosses,
+            reason: RowOrColumn,
+        });
+        assert_eq!(pos.play(win).unwrap(), result);
+        assert_eq!(pos.result(), result);
+        assert!(pos.play("c1".parse().unwrap()).is_err());
+
+        assert_eq!(pos.undo().unwrap(), win);
+        assert_eq!(pos.result(), None);
+        assert_eq!(pos.turn(), Crosses);
+        assert_eq!(pos.last_move(), Some(moves[3]));
+        for &m in moves.iter().rev() {
+            assert_eq!(pos.undo().unwrap(), m);
+        }
+        assert_eq!(*pos.pos(), State::new());
+        assert!(pos.undo().is_err());
+
+        // You play x1 as crosses, the machine replies o1, you swap sides and the machine plays
+        // x2. Undoing your move takes back both moves since.
+        let [x1, o1, x2] = ["a1", "b2", "c3"].map(|m| m.parse::<Move>().unwrap());
+        for m in [x1, o1, x2] {
+            pos.play(m).unwrap();
+        }
+        assert_eq!(pos.undo_by(Naughts).unwrap(), o1);
+        assert_eq!(pos.history().len(), 1);
+        assert_eq!(pos.turn(), Naughts);
+        assert!(pos.undo_by(Naughts).is_err());
+        assert_eq!(pos.history().len(), 1);
+        assert_eq!(pos.undo_by(Crosses).unwrap(), x1);
+        assert!(pos.undo_by(Crosses).is_err());
+
+        let from_state = Position::from("XX./OO./... x".parse::<State>().unwrap());
+        assert_eq!(from_state.turn(), Crosses);
+        assert_eq!(from_state.result(), None);
+    }
+}
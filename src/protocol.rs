@@ -0,0 +1,315 @@
+use crate::*;
+use anyhow::Context as _;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, ToSocketAddrs};
+use std::str::FromStr;
+
+/// A command sent to an agent in the line protocol.
+///
+/// Every command is a single line:
+///
+/// - `newgame`: start a new game from the initial position.
+/// - `isready`: the agent answers `readyok` when it can accept commands.
+/// - `position startpos [moves a1 b2 ...]` or `position X.O/.X./..O [moves ...]`: set up a
+///   position.
+/// - `go`: the agent answers `bestmove <move>` or `bestmove resign`.
+/// - `result x row`, `result o resign`, `result draw full`, ...: the game is over. The reasons
+///   for winning are `row`, `diagonal`, `resign`, `timeout`, `illegal` and `forfeit`, the
+///   reasons for a draw are `full`, `agreement` and `adjudication`.
+/// - `quit`: end the session.
+///
+/// Invalid commands are answered with `error <message>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Request {
+    NewGame,
+    IsReady,
+    SetPosition { start: State, moves: Vec<Move> },
+    Go,
+    GameOver(GameResult),
+    Quit,
+}
+
+/// An answer from an agent in the line protocol.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Response {
+    ReadyOk,
+    /// The selected move, or `None` for a resignation.
+    BestMove(Option<Move>),
+    Error(String),
+}
+
+/// The protocol names of the reasons for winning.
+const WIN_REASONS: [(WinReason, &str); 6] = [
+    (RowOrColumn, "row"),
+    (Diagonal, "diagonal"),
+    (Resignation, "resign"),
+    (Timeout, "timeout"),
+    (IllegalMove, "illegal"),
+    (Forfeit, "forfeit"),
+];
+
+/// The protocol names of the reasons for a draw.
+const DRAW_REASONS: [(DrawReason, &str); 3] = [
+    (BoardFull, "full"),
+    (Agreement, "agreement"),
+    (Adjudication, "adjudication"),
+];
+
+fn format_result(result: GameResult) -> String {
+    match result {
+        Draw { reason } => {
+            let &(_, reason) = DRAW_REASONS.iter().find(|&&(r, _)| r == reason).unwrap();
+            format!("draw {reason}")
+        }
+        Win { winner, reason } => {
+            let winner = Mark::from(winner).to_char().to_ascii_lowercase();
+            let &(_, reason) = WIN_REASONS.iter().find(|&&(r, _)| r == reason).unwrap();
+            format!("{winner} {reason}")
+        }
+    }
+}
+
+fn parse_result(s: &str) -> anyhow::Result<GameResult> {
+    let (winner, reason) = s.split_once(' ').context(
+        "A result should be a winner or \"draw\" and a reason, like \"x row\" or \"draw full\".",
+    )?;
+    let winner = match winner {
+        "draw" => {
+            let &(reason, _) = DRAW_REASONS
+                .iter()
+                .find(|&&(_, name)| name == reason)
+                .with_context(|| format!("Unknown reason for a draw \"{reason}\"."))?;
+            return Ok(Draw { reason });
+        }
+        "x" => Crosses,
+        "o" => Naughts,
+        _ => anyhow::bail!("The winner must be x, o or draw but got \"{winner}\"."),
+    };
+    let &(reason, _) = WIN_REASONS
+        .iter()
+        .find(|&&(_, name)| name == reason)
+        .with_context(|| format!("Unknown reason for winning \"{reason}\"."))?;
+    Ok(Win { winner, reason })
+}
+
+impl fmt::Display for Request {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Request::NewGame => write!(f, "newgame"),
+            Request::IsReady => write!(f, "isready"),
+            Request::SetPosition { start, moves } => {
+                if *start == State::new() {
+                    write!(f, "position startpos")?;
+                } else {
+                    write!(f, "position {start}")?;
+                }
+                if !moves.is_empty() {
+                    write!(f, " moves")?;
+                    for m in moves {
+                        write!(f, " {m}")?;
+                    }
+                }
+                Ok(())
+            }
+            Request::Go => write!(f, "go"),
+            Request::GameOver(result) => write!(f, "result {}", format_result(*result)),
+            Request::Quit => write!(f, "quit"),
+        }
+    }
+}
+
+impl FromStr for Request {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let s = s.trim();
+        let (command, args) = s.split_once(' ').unwrap_or((s, ""));
+        let args = args.trim();
+        Ok(match command {
+            "newgame" => Request::NewGame,
+            "isready" => Request::IsReady,
+            "position" => {
+                let (setup, moves) = match args.split_once("moves") {
+                    Some((setup, moves)) => (setup.trim(), moves),
+                    None => (args, ""),
+                };
+                let start = match setup {
+                    "startpos" => State::new(),
+                    _ => setup.parse()?,
+                };
+                let moves = moves
+                    .split_whitespace()
+                    .map(str::parse)
+                    .collect::<anyhow::Result<_>>()?;
+                Request::SetPosition { start, moves }
+            }
+            "go" => Request::Go,
+            "result" => Request::GameOver(parse_result(args)?),
+            "quit" => Request::Quit,
+            _ => anyhow::bail!("Unknown command \"{command}\"."),
+        })
+    }
+}
+
+impl fmt::Display for Response {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Response::ReadyOk => write!(f, "readyok"),
+            Response::BestMove(Some(m)) => write!(f, "bestmove {m}"),
+            Response::BestMove(None) => write!(f, "bestmove resign"),
+            Response::Error(message) => write!(f, "error {message}"),
+        }
+    }
+}
+
+impl FromStr for Response {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let s = s.trim();
+        let (command, args) = s.split_once(' ').unwrap_or((s, ""));
+        Ok(match (command, args.trim()) {
+            ("readyok", "") => Response::ReadyOk,
+            ("bestmove", "resign") => Response::BestMove(None),
+            ("bestmove", m) => Response::BestMove(Some(m.parse()?)),
+            ("error", message) => Response::Error(message.to_string()),
+            _ => anyhow::bail!("Unknown response \"{s}\"."),
+        })
+    }
+}
+
+/// Set up a position from the initial position and moves of a `position` command.
+fn set_up(start: State, moves: &[Move]) -> anyhow::Result<Position> {
+    let mut pos = Position::from(start);
+    for &m in moves {
+        pos.play(m)?;
+    }
+    Ok(pos)
+}
+
+/// Let an agent answer commands read from `input` until `quit` or the end of the input.
+pub fn serve<A: Agent>(
+    agent: &mut A,
+    input: impl BufRead,
+    mut output: impl Write,
+) -> io::Result<()> {
+    let mut pos = Position::new();
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match line.parse::<Request>() {
+            Err(e) => Some(Response::Error(e.to_string())),
+            Ok(Request::NewGame) => {
+                agent.new_game();
+                pos = Position::new();
+                None
+            }
+            Ok(Request::IsReady) => Some(Response::ReadyOk),
+            Ok(Request::SetPosition { start, moves }) => match set_up(start, &moves) {
+                Ok(new_pos) => {
+                    pos = new_pos;
+                    None
+                }
+                Err(e) => Some(Response::Error(e.to_string())),
+            },
+            Ok(Request::Go) if pos.result().is_some() => {
+                Some(Response::Error("The game is already over.".to_string()))
+            }
+            Ok(Request::Go) => Some(match agent.select_move(*pos.pos(), pos.turn()) {
+                Ok(m) => Response::BestMove(m),
+                Err(reason) => Response::Error(format!("The agent forfeits by {reason}.")),
+            }),
+            Ok(Request::GameOver(result)) => {
+                agent.game_over(result);
+                None
+            }
+            Ok(Request::Quit) => break,
+        };
+        if let Some(response) = response {
+            writeln!(output, "{response}")?;
+            output.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// Listen on `addr` and serve one connection at a time. Errors of a connection, like a client
+/// disconnecting in the middle of a game, are logged and the next connection is served.
+pub fn serve_tcp<A: Agent>(agent: &mut A, addr: impl ToSocketAddrs) -> io::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    for stream in listener.incoming() {
+        let res = stream.and_then(|stream| {
+            let peer = stream.peer_addr()?;
+            serve(agent, BufReader::new(stream.try_clone()?), stream)
+                .map_err(|e| io::Error::new(e.kind(), format!("{peer}: {e}")))
+        });
+        if let Err(e) = res {
+            eprintln!("Error: {e}");
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notation() {
+        for s in [
+            "newgame",
+            "isready",
+            "position startpos",
+            "position X../.O./... x moves c3 a3",
+            "go",
+            "result draw agreement",
+            "result x illegal",
+            "result o resign",
+            "quit",
+        ] {
+            assert_eq!(s.parse::<Request>().unwrap().to_string(), s);
+        }
+        for s in ["readyok", "bestmove b2", "bestmove resign", "error Bad."] {
+            assert_eq!(s.parse::<Response>().unwrap().to_string(), s);
+        }
+        assert!("position startpos moves a1 a1".parse::<Request>().is_ok());
+        assert!("position startpos moves d1".parse::<Request>().is_err());
+        assert!("result x".parse::<Request>().is_err());
+        assert!("move a1".parse::<Request>().is_err());
+    }
+
+    #[test]
+    fn test_serve() {
+        let input = "isready\n\
+                     newgame\n\
+                     go\n\
+                     position startpos moves a1\n\
+                     go\n\
+                     position startpos moves a1 a1\n\
+                     position XX./OO./... x\n\
+                     go\n\
+                     position XXX/OO./... o\n\
+                     go\n\
+                     result x row\n\
+                     quit\n\
+                     go\n";
+        let mut output = Vec::new();
+        serve(&mut Solver::new(), input.as_bytes(), &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let lines = output.lines().collect::<Vec<_>>();
+        assert_eq!(
+            lines[..4],
+            [
+                "readyok",
+                "bestmove a1",
+                "bestmove b2",
+                "error The move a1 is not a legal move in this position."
+            ]
+        );
+        assert_eq!(
+            lines[4..],
+            ["bestmove a3", "error The game is already over."]
+        );
+    }
+}
@@ -0,0 +1,121 @@
+use crate::*;
+use std::fmt;
+
+/// The width of a cell in characters.
+const CELL_WIDTH: usize = 5;
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const UNDERLINE: &str = "\x1b[4m";
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const BLUE: &str = "\x1b[34m";
+const DIM: &str = "\x1b[2m";
+
+/// A tic-tac-toe board rendered for the terminal with row letters and column numbers.
+///
+/// Without colours the last move is shown in brackets, like "[X]", and the winning line between
+/// stars, like "*X*".
+#[derive(Debug, Copy, Clone)]
+pub struct BoardView<'a> {
+    pub pos: &'a State,
+    /// Use ANSI escape codes for colours.
+    pub colors: bool,
+    /// A move to highlight.
+    pub last_move: Option<Move>,
+    /// Print the probability for every empty cell.
+    pub scores: Option<&'a MoveScores>,
+}
+
+impl<'a> BoardView<'a> {
+    /// A view without colours, highlighted moves and scores.
+    pub fn new(pos: &'a State) -> Self {
+        Self {
+            pos,
+            colors: false,
+            last_move: None,
+            scores: None,
+        }
+    }
+
+    /// The visible text of a cell and the escape codes to colour it with.
+    fn cell(&self, m: Move, winning_line: Option<[Move; 3]>) -> (String, String) {
+        let mark = self.pos[m];
+        let mut text = match (mark, self.scores) {
+            (Blank, Some(scores)) => format!("{:.0}%", scores.score(m) * 100.0),
+            _ => mark.to_char().to_string(),
+        };
+        let mut codes = match mark {
+            Cross => RED.to_string(),
+            Naught => BLUE.to_string(),
+            Blank => DIM.to_string(),
+        };
+        if winning_line.is_some_and(|line| line.contains(&m)) {
+            text = format!("*{text}*");
+            codes = format!("{BOLD}{GREEN}");
+        } else if self.last_move == Some(m) {
+            text = format!("[{text}]");
+            codes += BOLD;
+            codes += UNDERLINE;
+        }
+        (text, codes)
+    }
+}
+
+impl fmt::Display for BoardView<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, " ")?;
+        for col in 1..=3 {
+            write!(f, " {col:^CELL_WIDTH$}")?;
+        }
+        writeln!(f)?;
+        let winning_line = self.pos.winning_line();
+        for row in 0..3 {
+            if row > 0 {
+                writeln!(f, "  {}", vec!["-".repeat(CELL_WIDTH); 3].join("+"))?;
+            }
+            write!(f, "{} ", char::from_u32('a' as u32 + row as u32).unwrap())?;
+            for col in 0..3 {
+                if col > 0 {
+                    write!(f, "|")?;
+                }
+                let (text, codes) = self.cell(Move::from_usize(row * 3 + col), winning_line);
+                if self.colors {
+                    // Pad outside of the escape codes so that only the text is highlighted.
+                    let padding = CELL_WIDTH.saturating_sub(text.chars().count());
+                    let left = " ".repeat(padding / 2);
+                    let right = " ".repeat(padding - padding / 2);
+                    write!(f, "{left}{codes}{text}{RESET}{right}")?;
+                } else {
+                    write!(f, "{text:^CELL_WIDTH$}")?;
+                }
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_board_view() {
+        let pos = "X.O/.XO/..X o".parse::<State>().unwrap();
+        let mut view = BoardView::new(&pos);
+        view.last_move = Some("b3".parse().unwrap());
+        assert_eq!(
+            view.to_string(),
+            "    1     2     3  \n\
+             a  *X* |  .  |  O  \n  \
+             -----+-----+-----\n\
+             b   .  | *X* | [O] \n  \
+             -----+-----+-----\n\
+             c   .  |  .  | *X* \n"
+        );
+        let scores = MoveScores::initial(&pos);
+        view.scores = Some(&scores);
+        assert!(view.to_string().contains("| 25% |"));
+    }
+}
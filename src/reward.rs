@@ -0,0 +1,203 @@
+use crate::*;
+use getset::{CopyGetters, Getters, Setters};
+use std::collections::HashMap;
+use std::fmt;
+
+const DECISIVE_FACTOR: f64 = 32.0;
+const DRAW_FACTOR: f64 = 0.9;
+
+/// The moves and the result of a finished game.
+#[derive(Debug, Clone, Getters, CopyGetters, Setters)]
+pub struct GameRecord<G: Game> {
+    /// The positions before every move together with the moves, starting with a move by crosses.
+    #[getset(get = "pub")]
+    moves: Vec<(G, G::Move)>,
+    #[getset(get_copy = "pub")]
+    result: GameResult,
+    /// The position and the move which a resigning player would have played.
+    #[getset(get_copy = "pub", set = "pub")]
+    resignation: Option<(G, G::Move)>,
+}
+
+impl<G: Game> GameRecord<G> {
+    pub fn new(moves: Vec<(G, G::Move)>, result: GameResult) -> Self {
+        Self {
+            moves,
+            result,
+            resignation: None,
+        }
+    }
+
+    /// The number of moves made by a player.
+    pub fn moves_by(&self, player: Player) -> usize {
+        match player {
+            Crosses => self.moves.len().div_ceil(2),
+            Naughts => self.moves.len() / 2,
+        }
+    }
+
+    /// Check if `resignation` is the move of a player.
+    pub fn resigned(&self, player: Player) -> bool {
+        // The player who resigned would have made the next move.
+        self.resignation.is_some() && player == turn_at(self.moves.len())
+    }
+
+    /// The moves of a player which are rewarded in order: the moves made by the player, followed
+    /// by the resignation if the player resigned.
+    pub fn moves_of(&self, player: Player) -> Vec<(G, G::Move)> {
+        let first = match player {
+            Crosses => 0,
+            Naughts => 1,
+        };
+        let mut moves = self
+            .moves
+            .iter()
+            .skip(first)
+            .step_by(2)
+            .copied()
+            .collect::<Vec<_>>();
+        if self.resigned(player) {
+            moves.extend(self.resignation);
+        }
+        moves
+    }
+}
+
+/// Decides how much the moves of a player are rewarded after a training game.
+pub trait Reward<G: Game = State>: fmt::Debug {
+    /// The factors for the scores of the moves in `record.moves_of(player)`, above 1.0 to reward
+    /// a move and below 1.0 to punish it. How the factors of later moves are passed on to
+    /// earlier moves is up to the machine's `CreditAssignment`.
+    ///
+    /// # Panics
+    ///
+    /// The machine panics in `play_training_match` if the number of factors differs from the
+    /// number of moves in `record.moves_of(player)`.
+    fn factors(&self, record: &GameRecord<G>, player: Player) -> Vec<f64>;
+}
+
+/// A reward for the last move of a player, which can prefer quick wins and long losses, and
+/// weigh the reasons for winning. The earlier moves get a factor of 1.0.
+///
+/// The default gives every win the same reward, like the original MENACE.
+#[derive(Debug, Clone)]
+pub struct ShapedReward {
+    /// The factor for a win. The loser gets the inverse.
+    pub decisive: f64,
+    /// The factor for both players after a draw.
+    pub draw: f64,
+    /// Every move made by a player before the last one raises the factor of a win or a loss to
+    /// this power, so quick wins are rewarded more and long losses are punished less. 1.0
+    /// ignores the length of the game.
+    pub length_decay: f64,
+    /// The powers the factors of wins and losses are raised to for a reason, like 0.5 for
+    /// resignations. Missing reasons have a weight of 1.0.
+    pub reason_weights: HashMap<WinReason, f64>,
+}
+
+impl Default for ShapedReward {
+    fn default() -> Self {
+        Self {
+            decisive: DECISIVE_FACTOR,
+            draw: DRAW_FACTOR,
+            length_decay: 1.0,
+            reason_weights: HashMap::new(),
+        }
+    }
+}
+
+impl ShapedReward {
+    /// The factor for the last move of `player`.
+    fn factor<G: Game>(&self, record: &GameRecord<G>, player: Player) -> f64 {
+        match record.result() {
+            Draw { .. } => self.draw,
+            Win { winner, reason } => {
+                let moves = record.moves_by(player).saturating_sub(1);
+                let weight = self.reason_weights.get(&reason).copied().unwrap_or(1.0)
+                    * self.length_decay.powi(moves as i32);
+                let factor = self.decisive.powf(weight);
+                if winner == player {
+                    factor
+                } else {
+                    1.0 / factor
+                }
+            }
+        }
+    }
+}
+
+impl<G: Game> Reward<G> for ShapedReward {
+    fn factors(&self, record: &GameRecord<G>, player: Player) -> Vec<f64> {
+        let moves = record.moves_by(player) + usize::from(record.resigned(player));
+        let mut factors = vec![1.0; moves];
+        if let Some(last) = factors.last_mut() {
+            *last = self.factor(record, player);
+        }
+        factors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shaped_reward() {
+        // Crosses wins in three moves.
+        let moves = ["a1", "b1", "a2", "b2", "a3"];
+        let mut pos = State::new();
+        let mut record = Vec::new();
+        for (i, m) in moves.iter().enumerate() {
+            let m = m.parse().unwrap();
+            record.push((pos, m));
+            pos.play(m, if i % 2 == 0 { Crosses } else { Naughts });
+        }
+        let win = Win {
+            winner: Crosses,
+            reason: RowOrColumn,
+        };
+        let record = GameRecord::new(record, win);
+        assert_eq!(record.moves_by(Crosses), 3);
+        assert_eq!(record.moves_by(Naughts), 2);
+        assert_eq!(
+            record.moves_of(Naughts),
+            [record.moves()[1], record.moves()[3]]
+        );
+
+        let mut reward = ShapedReward::default();
+        assert_eq!(reward.factors(&record, Crosses), [1.0, 1.0, 32.0]);
+        assert_eq!(reward.factors(&record, Naughts), [1.0, 1.0 / 32.0]);
+
+        reward.length_decay = 0.5;
+        let last = |reward: &ShapedReward, record: &GameRecord<State>, player| {
+            *reward.factors(record, player).last().unwrap()
+        };
+        assert!((last(&reward, &record, Crosses) - 32f64.powf(0.25)).abs() < 1e-12);
+        assert!((last(&reward, &record, Naughts) - 32f64.powf(-0.5)).abs() < 1e-12);
+
+        reward.reason_weights.insert(RowOrColumn, 2.0);
+        assert!((last(&reward, &record, Crosses) - 32f64.powf(0.5)).abs() < 1e-12);
+
+        let draw = GameRecord::new(record.moves().clone(), Draw { reason: Agreement });
+        assert_eq!(reward.factors(&draw, Naughts), [1.0, 0.9]);
+
+        // Naughts resigns instead of making a second move.
+        let mut resigned = GameRecord::new(
+            record.moves()[..3].to_vec(),
+            Win {
+                winner: Crosses,
+                reason: Resignation,
+            },
+        );
+        resigned.set_resignation(Some(record.moves()[3]));
+        assert_eq!(
+            resigned.moves_of(Naughts),
+            [record.moves()[1], record.moves()[3]]
+        );
+        assert_eq!(resigned.moves_of(Crosses).len(), 2);
+        assert!(resigned.resigned(Naughts) && !resigned.resigned(Crosses));
+        reward.reason_weights.insert(Resignation, 0.5);
+        assert_eq!(reward.factors(&resigned, Naughts)[0], 1.0);
+        assert!((last(&reward, &resigned, Naughts) - 32f64.powf(-0.5)).abs() < 1e-12);
+    }
+}
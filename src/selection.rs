@@ -0,0 +1,159 @@
+use anyhow::Context as _;
+use std::fmt;
+use std::str::FromStr;
+
+/// How the machine selects a move from the move scores.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum Selection {
+    /// Select every move with a probability equal to its score.
+    #[default]
+    Proportional,
+    /// Always select the move with the highest score.
+    Greedy,
+    /// Select every move with a probability proportional to `score^(1 / temperature)`. A
+    /// temperature of 1.0 is proportional selection, lower temperatures get closer to greedy
+    /// selection and higher temperatures get closer to uniformly random moves.
+    Softmax { temperature: f64 },
+    /// Select a uniformly random legal move with probability `epsilon`, otherwise the move with
+    /// the highest score.
+    EpsilonGreedy { epsilon: f64 },
+}
+
+impl fmt::Display for Selection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Selection::Proportional => write!(f, "proportional"),
+            Selection::Greedy => write!(f, "greedy"),
+            Selection::Softmax { temperature } => write!(f, "softmax {temperature}"),
+            Selection::EpsilonGreedy { epsilon } => write!(f, "epsilon {epsilon}"),
+        }
+    }
+}
+
+impl FromStr for Selection {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let words = s.split_whitespace().collect::<Vec<_>>();
+        let parameter = |name: &str| -> anyhow::Result<f64> {
+            let value = words
+                .get(1)
+                .with_context(|| format!("Missing the {name}."))?
+                .parse::<f64>()
+                .with_context(|| format!("The {name} should be a number."))?;
+            anyhow::ensure!(
+                value.is_finite() && value >= 0.0,
+                "The {name} should not be negative."
+            );
+            Ok(value)
+        };
+        let selection = match words.first().copied() {
+            Some("proportional") => Selection::Proportional,
+            Some("greedy") => Selection::Greedy,
+            Some("softmax") => {
+                let temperature = parameter("temperature")?;
+                anyhow::ensure!(temperature > 0.0, "The temperature should be above 0.");
+                Selection::Softmax { temperature }
+            }
+            Some("epsilon") => {
+                let epsilon = parameter("epsilon")?;
+                anyhow::ensure!(epsilon <= 1.0, "The epsilon should be at most 1.");
+                Selection::EpsilonGreedy { epsilon }
+            }
+            _ => anyhow::bail!(
+                "A selection mode should be proportional, greedy, softmax <temperature> or \
+                 epsilon <epsilon>."
+            ),
+        };
+        let expected_words = match selection {
+            Selection::Proportional | Selection::Greedy => 1,
+            _ => 2,
+        };
+        anyhow::ensure!(
+            words.len() == expected_words,
+            "Unexpected \"{}\" after the selection mode.",
+            words[expected_words..].join(" ")
+        );
+        Ok(selection)
+    }
+}
+
+/// Selection modes which change during training.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schedule {
+    /// (games, selection): use the selection once this many training games have been played.
+    /// Ordered by games, starting with 0.
+    steps: Vec<(u64, Selection)>,
+}
+
+impl Default for Schedule {
+    fn default() -> Self {
+        Self::constant(Selection::default())
+    }
+}
+
+impl Schedule {
+    /// Always use the same selection.
+    pub fn constant(selection: Selection) -> Self {
+        Self {
+            steps: vec![(0, selection)],
+        }
+    }
+
+    /// Start with `first` and switch selection after the given numbers of games, like
+    /// `Schedule::new(Selection::Softmax { temperature: 2.0 }, [(100000, Selection::Greedy)])`.
+    pub fn new(first: Selection, steps: impl IntoIterator<Item = (u64, Selection)>) -> Self {
+        let mut res = Self::constant(first);
+        for (games, selection) in steps {
+            assert!(
+                res.steps.last().unwrap().0 < games,
+                "The steps of a schedule should be ordered by the number of games."
+            );
+            res.steps.push((games, selection));
+        }
+        res
+    }
+
+    /// The selection after a number of training games.
+    pub fn at(&self, games: u64) -> Selection {
+        let i = self.steps.partition_point(|&(start, _)| start <= games);
+        self.steps[i - 1].1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notation() {
+        for s in ["proportional", "greedy", "softmax 0.5", "epsilon 0.1"] {
+            assert_eq!(s.parse::<Selection>().unwrap().to_string(), s);
+        }
+        for s in [
+            "",
+            "softmax",
+            "softmax 0",
+            "epsilon 2",
+            "greedy 1",
+            "epsilon -0.1",
+        ] {
+            assert!(s.parse::<Selection>().is_err(), "{s}");
+        }
+    }
+
+    #[test]
+    fn test_schedule() {
+        let schedule = Schedule::new(
+            Selection::Softmax { temperature: 2.0 },
+            [
+                (100, Selection::Proportional),
+                (1000, Selection::EpsilonGreedy { epsilon: 0.1 }),
+            ],
+        );
+        assert_eq!(schedule.at(0), Selection::Softmax { temperature: 2.0 });
+        assert_eq!(schedule.at(99), Selection::Softmax { temperature: 2.0 });
+        assert_eq!(schedule.at(100), Selection::Proportional);
+        assert_eq!(schedule.at(5000), Selection::EpsilonGreedy { epsilon: 0.1 });
+        assert_eq!(Schedule::default().at(5000), Selection::Proportional);
+    }
+}
@@ -0,0 +1,103 @@
+use crate::*;
+use std::collections::HashMap;
+
+/// The outcome of a position with perfect play, seen from the player to move.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Outcome {
+    Lost,
+    Drawn,
+    Won,
+}
+pub use Outcome::*;
+
+impl Outcome {
+    /// The outcome seen from the other player.
+    pub fn flip(self) -> Self {
+        match self {
+            Lost => Won,
+            Drawn => Drawn,
+            Won => Lost,
+        }
+    }
+}
+
+/// A solver which finds the outcome of positions by searching the full game tree.
+///
+/// Solved positions are cached, so this is only feasible for small games like tic-tac-toe.
+#[derive(Debug, Clone)]
+pub struct Solver<G: Game = State> {
+    outcomes: HashMap<(G, Player), Outcome>,
+}
+
+impl<G: Game> Default for Solver<G> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<G: Game> Solver<G> {
+    pub fn new() -> Self {
+        Self {
+            outcomes: HashMap::new(),
+        }
+    }
+
+    /// The outcome of a move by `turn`.
+    fn outcome_after(&mut self, pos: G, turn: Player, m: G::Move) -> Outcome {
+        let mut child = pos;
+        child.play(m, turn);
+        match child.result(turn) {
+            Some(Draw { .. }) => Drawn,
+            Some(Win { winner, .. }) if winner == turn => Won,
+            Some(Win { .. }) => Lost,
+            None => self.outcome(child, turn.opponent()).flip(),
+        }
+    }
+
+    /// The outcome for the player to move in a position where the game is not over.
+    pub fn outcome(&mut self, pos: G, turn: Player) -> Outcome {
+        if let Some(&outcome) = self.outcomes.get(&(pos, turn)) {
+            return outcome;
+        }
+        let mut best = Lost;
+        for m in pos.legal_moves() {
+            best = best.max(self.outcome_after(pos, turn, m));
+            if best == Won {
+                break;
+            }
+        }
+        self.outcomes.insert((pos, turn), best);
+        best
+    }
+
+    /// All moves which keep the best outcome for the player to move.
+    pub fn best_moves(&mut self, pos: G, turn: Player) -> Vec<G::Move> {
+        let best = self.outcome(pos, turn);
+        pos.legal_moves()
+            .filter(|&m| self.outcome_after(pos, turn, m) == best)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solver() {
+        let mut solver = Solver::new();
+        assert_eq!(solver.outcome(State::new(), Crosses), Drawn);
+        // Every first move draws with perfect play.
+        assert_eq!(solver.best_moves(State::new(), Crosses).len(), Move::N);
+
+        // Naughts must take the centre after a corner opening.
+        let pos = "X../.../... o".parse::<State>().unwrap();
+        assert_eq!(solver.best_moves(pos, Naughts), ["b2".parse().unwrap()]);
+
+        // Naughts loses after answering a corner opening with an adjacent edge.
+        let pos = "XO./.../... x".parse::<State>().unwrap();
+        assert_eq!(solver.outcome(pos, Crosses), Won);
+        let pos = "XO./X../... o".parse::<State>().unwrap();
+        assert_eq!(solver.outcome(pos, Naughts), Lost);
+    }
+}
@@ -0,0 +1,122 @@
+use crate::*;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Counts of game results, used to report how training is going.
+#[derive(Debug, Clone, Default)]
+pub struct ResultStats {
+    counts: HashMap<GameResult, u32>,
+    games: u32,
+}
+
+impl ResultStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count a game.
+    pub fn add(&mut self, result: GameResult) {
+        *self.counts.entry(result).or_default() += 1;
+        self.games += 1;
+    }
+
+    /// The number of counted games.
+    pub fn games(&self) -> u32 {
+        self.games
+    }
+
+    /// The percentage of games with a result matching `filter`.
+    pub fn percent(&self, filter: impl Fn(GameResult) -> bool) -> f32 {
+        if self.games == 0 {
+            return 0.0;
+        }
+        self.counts
+            .iter()
+            .filter(|&(&r, _)| filter(r))
+            .map(|(_, &c)| c as f32)
+            .sum::<f32>()
+            / self.games as f32
+            * 100.0
+    }
+
+    /// The percentage of draws.
+    pub fn draws(&self) -> f32 {
+        self.percent(|r| matches!(r, Draw { .. }))
+    }
+
+    /// The percentage of games won by a player.
+    pub fn wins(&self, player: Player) -> f32 {
+        self.percent(|r| matches!(r, Win { winner, .. } if winner == player))
+    }
+
+    /// The percentage of games won for a reason.
+    pub fn wins_by(&self, reason: WinReason) -> f32 {
+        self.percent(|r| matches!(r, Win { reason: r, .. } if r == reason))
+    }
+
+    /// The percentage of games drawn for a reason.
+    pub fn draws_by(&self, reason: DrawReason) -> f32 {
+        self.percent(|r| r == Draw { reason })
+    }
+
+    /// The percentage of games ending with a resignation.
+    pub fn resignations(&self) -> f32 {
+        self.wins_by(Resignation)
+    }
+}
+
+/// Shows the percentages of draws and wins, followed by the percentages of the reasons which
+/// occurred.
+impl fmt::Display for ResultStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "draws: {:.1}, wins: crosses: {:.1}, naughts: {:.1}",
+            self.draws(),
+            self.wins(Crosses),
+            self.wins(Naughts),
+        )?;
+        let reasons = WinReason::ALL
+            .iter()
+            .map(|&reason| (reason.to_string(), self.wins_by(reason)))
+            .chain(
+                DrawReason::ALL
+                    .iter()
+                    .map(|&reason| (format!("draw by {reason}"), self.draws_by(reason))),
+            );
+        for (reason, percent) in reasons {
+            if percent > 0.0 {
+                write!(f, ", {reason}: {percent:.1}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let mut stats = ResultStats::new();
+        stats.add(Draw { reason: BoardFull });
+        stats.add(Win {
+            winner: Crosses,
+            reason: Diagonal,
+        });
+        stats.add(Win {
+            winner: Naughts,
+            reason: Timeout,
+        });
+        stats.add(Win {
+            winner: Naughts,
+            reason: Timeout,
+        });
+        assert_eq!(
+            stats.to_string(),
+            "draws: 25.0, wins: crosses: 25.0, naughts: 50.0, diagonal: 25.0, timeout: 50.0, \
+             draw by full board: 25.0"
+        );
+    }
+}
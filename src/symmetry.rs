@@ -0,0 +1,62 @@
+use crate::*;
+
+/// A map from a row and a column to another row and column.
+type CellMap = fn(usize, usize) -> (usize, usize);
+
+/// One of the eight rotations and reflections of the tic-tac-toe board.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Symmetry {
+    /// cells[m] = the cell which m is mapped to:
+    cells: [Move; Move::N],
+}
+
+impl Symmetry {
+    /// All symmetries, starting with the identity.
+    pub fn all() -> impl Iterator<Item = Self> {
+        let maps: [CellMap; 8] = [
+            |r, c| (r, c),
+            |r, c| (c, 2 - r),
+            |r, c| (2 - r, 2 - c),
+            |r, c| (2 - c, r),
+            |r, c| (r, 2 - c),
+            |r, c| (2 - r, c),
+            |r, c| (c, r),
+            |r, c| (2 - c, 2 - r),
+        ];
+        maps.into_iter().map(|map| Self {
+            cells: std::array::from_fn(|i| {
+                let (r, c) = map(i / 3, i % 3);
+                Move::from_usize(r * 3 + c)
+            }),
+        })
+    }
+
+    /// Map a move to its image.
+    pub fn apply_move(self, m: Move) -> Move {
+        self.cells[m.to_usize()]
+    }
+
+    /// Map a position to its image.
+    pub fn apply(self, pos: &State) -> State {
+        let mut res = State::new();
+        for m in Move::all() {
+            res[self.apply_move(m)] = pos[m];
+        }
+        res
+    }
+}
+
+impl State {
+    /// A number uniquely identifying the position.
+    fn key(&self) -> u32 {
+        Move::all().fold(0, |key, m| key * 3 + self[m] as u32)
+    }
+
+    /// The representative of all symmetric images of the position.
+    pub fn canonical(&self) -> State {
+        Symmetry::all()
+            .map(|sym| sym.apply(self))
+            .min_by_key(State::key)
+            .unwrap()
+    }
+}
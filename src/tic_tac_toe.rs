@@ -1,12 +1,26 @@
 use crate::*;
-use std::convert::identity;
+use anyhow::Context as _;
 use std::fmt;
 use std::mem::transmute;
 use std::ops;
+use std::str::FromStr;
 
 const CROSS_I: u8 = 0;
 const NAUGHT_I: u8 = 1;
 
+/// The cell indices of all rows, columns and diagonals, with a flag telling if the line is a
+/// diagonal.
+pub(crate) const LINES: [([usize; 3], bool); 8] = [
+    ([0, 1, 2], false),
+    ([3, 4, 5], false),
+    ([6, 7, 8], false),
+    ([0, 3, 6], false),
+    ([1, 4, 7], false),
+    ([2, 5, 8], false),
+    ([0, 4, 8], true),
+    ([2, 4, 6], true),
+];
+
 /// A mark in tic-tac-toe.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 #[repr(u8)]
@@ -27,6 +41,9 @@ pub enum Player {
 pub use Player::*;
 
 /// A state in tic-tac-toe.
+///
+/// Written as the rows separated by slashes followed by the side to move, like "X.O/.X./..O o".
+/// The side to move is optional when parsing.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub struct State([[Mark; 3]; 3]);
 
@@ -61,6 +78,27 @@ impl PartialEq<Mark> for Player {
     }
 }
 
+impl Mark {
+    /// The character for the mark in the position notation: 'X', 'O' or '.'.
+    pub fn to_char(self) -> char {
+        match self {
+            Cross => 'X',
+            Naught => 'O',
+            Blank => '.',
+        }
+    }
+
+    /// Parse a character in the position notation. Accepts lower case letters.
+    pub fn from_char(c: char) -> anyhow::Result<Self> {
+        match c {
+            'X' | 'x' => Ok(Cross),
+            'O' | 'o' => Ok(Naught),
+            '.' => Ok(Blank),
+            _ => anyhow::bail!("Bad mark '{c}', expected X, O or '.'."),
+        }
+    }
+}
+
 impl Player {
     pub fn opponent(self) -> Self {
         match self {
@@ -70,6 +108,77 @@ impl Player {
     }
 }
 
+impl fmt::Display for State {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, row) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, "/")?;
+            }
+            for m in row {
+                write!(f, "{}", m.to_char())?;
+            }
+        }
+        let turn = Mark::from(self.side_to_move());
+        write!(f, " {}", turn.to_char().to_ascii_lowercase())
+    }
+}
+
+impl FromStr for State {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let mut parts = s.split_whitespace();
+        let board = parts
+            .next()
+            .context("A position cannot be an empty string.")?;
+        let turn = parts.next();
+        anyhow::ensure!(
+            parts.next().is_none(),
+            "A position should be a board and an optional side to move, like \"X.O/.X./..O o\"."
+        );
+        let rows = board.split('/').collect::<Vec<_>>();
+        anyhow::ensure!(
+            rows.len() == 3,
+            "A position should have 3 rows separated by '/' but got {}.",
+            rows.len()
+        );
+        let mut pos = State::new();
+        for (row_i, row) in rows.into_iter().enumerate() {
+            let marks = row
+                .chars()
+                .map(Mark::from_char)
+                .collect::<Result<Vec<_>, _>>()?;
+            anyhow::ensure!(
+                marks.len() == 3,
+                "Every row in a position should have 3 marks but row {} has {}.",
+                row_i + 1,
+                marks.len()
+            );
+            pos.0[row_i].copy_from_slice(&marks);
+        }
+        pos.validate()?;
+        if let Some(turn) = turn {
+            let turn = match turn {
+                "X" | "x" => Crosses,
+                "O" | "o" => Naughts,
+                _ => anyhow::bail!("The side to move must be X or O but got \"{turn}\"."),
+            };
+            anyhow::ensure!(
+                turn == pos.side_to_move(),
+                "{turn} can not be the side to move with {} crosses and {} naughts.",
+                pos.count(Cross),
+                pos.count(Naught)
+            );
+        }
+        Ok(pos)
+    }
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ops::Index<Move> for State {
     type Output = Mark;
     fn index(&self, m: Move) -> &Self::Output {
@@ -89,18 +198,55 @@ impl State {
         State([[Blank; 3]; 3])
     }
 
+    /// The number of cells with the given mark.
+    fn count(&self, mark: Mark) -> usize {
+        self.0.iter().flatten().filter(|&&m| m == mark).count()
+    }
+
+    /// The player to move, derived from the number of marks. Crosses moves first.
+    pub fn side_to_move(&self) -> Player {
+        if self.count(Cross) > self.count(Naught) {
+            Naughts
+        } else {
+            Crosses
+        }
+    }
+
+    /// Check that the position can be reached from an empty board.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let (crosses, naughts) = (self.count(Cross), self.count(Naught));
+        anyhow::ensure!(
+            crosses == naughts || crosses == naughts + 1,
+            "Impossible position with {crosses} crosses and {naughts} naughts: crosses moves \
+             first and the players alternate."
+        );
+        let has_line =
+            |player| self.has_row(player) || self.has_column(player) || self.has_diagonal(player);
+        match (has_line(Crosses), has_line(Naughts)) {
+            (true, true) => {
+                anyhow::bail!("Impossible position where both players have three in a row.")
+            }
+            (true, false) => anyhow::ensure!(
+                crosses == naughts + 1,
+                "Impossible position where naughts moved after crosses had three in a row."
+            ),
+            (false, true) => anyhow::ensure!(
+                crosses == naughts,
+                "Impossible position where crosses moved after naughts had three in a row."
+            ),
+            (false, false) => (),
+        }
+        Ok(())
+    }
+
     /// Check if the given player has three in a row.
     pub fn has_row(&self, player: Player) -> bool {
-        (0..3)
-            .map(|i| self.0[i].iter().all(|&m| m == player))
-            .any(identity)
+        (0..3).any(|i| self.0[i].iter().all(|&m| m == player))
     }
 
     /// Check if the given player has three in a column.
     pub fn has_column(&self, player: Player) -> bool {
-        (0..3)
-            .map(|i| self.0.iter().map(|row| row[i] == player).all(identity))
-            .any(identity)
+        (0..3).any(|i| self.0.iter().all(|row| row[i] == player))
     }
 
     /// Check if the given player has three on a diagonal.
@@ -114,6 +260,14 @@ impl State {
         Move::all().all(|m| self[m] != Blank)
     }
 
+    /// Get a line of three equal marks if there is one.
+    pub fn winning_line(&self) -> Option<[Move; 3]> {
+        LINES
+            .iter()
+            .map(|(line, _)| line.map(Move::from_usize))
+            .find(|line| self[line[0]] != Blank && line.iter().all(|&m| self[m] == self[line[0]]))
+    }
+
     /// All legal moves in the position.  Garanteed to be ordered by `Move::to_usize()`.
     pub fn legal_moves(&self) -> MoveSet {
         MoveSet::from_fn(|m| self[m] == Blank)
@@ -125,6 +279,12 @@ impl State {
         self[m] = player.into();
     }
 
+    /// Take back a move.
+    pub fn unplay(&mut self, m: Move) {
+        debug_assert_ne!(self[m], Blank);
+        self[m] = Blank;
+    }
+
     /// Given the player who made the last move, return the result if the game is over.
     pub fn result(&self, player: Player) -> Option<GameResult> {
         if self.has_row(player) || self.has_column(player) {
@@ -138,9 +298,84 @@ impl State {
                 reason: Diagonal,
             })
         } else if self.is_draw() {
-            Some(Draw)
+            Some(Draw { reason: BoardFull })
         } else {
             None
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notation() {
+        let pos = "X.O/.X./..O x".parse::<State>().unwrap();
+        assert_eq!(pos.to_string(), "X.O/.X./..O x");
+        assert_eq!(pos["a1".parse().unwrap()], Cross);
+        assert_eq!(pos["a3".parse().unwrap()], Naught);
+        assert_eq!(pos["b1".parse().unwrap()], Blank);
+        assert_eq!(
+            "x../.o./... x".parse::<State>().unwrap().to_string(),
+            "X../.O./... x"
+        );
+        assert_eq!(State::new().to_string(), ".../.../... x");
+        assert_eq!(
+            "X../.../...".parse::<State>().unwrap().to_string(),
+            "X../.../... o"
+        );
+
+        // Malformed strings:
+        assert!("".parse::<State>().is_err());
+        assert!("X../...".parse::<State>().is_err());
+        assert!("X../..../...".parse::<State>().is_err());
+        assert!("X../.-./...".parse::<State>().is_err());
+        assert!("X../.../... o extra".parse::<State>().is_err());
+        assert!("X../.../... .".parse::<State>().is_err());
+        assert!("X../.../... ox".parse::<State>().is_err());
+
+        // Impossible mark counts and side to move:
+        assert!("XX./.../...".parse::<State>().is_err());
+        assert!("O../.../...".parse::<State>().is_err());
+        assert!("X../.../... x".parse::<State>().is_err());
+    }
+
+    #[test]
+    fn test_unplay() {
+        let mut pos = "X.O/.X./... o".parse::<State>().unwrap();
+        let before = pos;
+        let m = "c3".parse().unwrap();
+        pos.play(m, Naughts);
+        assert_eq!(pos.to_string(), "X.O/.X./..O x");
+        pos.unplay(m);
+        assert_eq!(pos, before);
+    }
+
+    #[test]
+    fn test_validate() {
+        let parse = |s: &str| {
+            let mut pos = State::new();
+            for (i, c) in s.chars().filter(|&c| c != '/').enumerate() {
+                pos[Move::from_usize(i)] = Mark::from_char(c).unwrap();
+            }
+            pos
+        };
+        for valid in ["XXX/OO./...", "OOO/XX./X..", "XOX/XOO/OXX", "XXX/OXO/XOO"] {
+            parse(valid).validate().unwrap();
+        }
+        for invalid in [
+            "XXX/XX./...",
+            "OO./.../...",
+            "XXX/OOO/...",
+            "XXX/OO./O..",
+            "OOO/XX./XX.",
+        ] {
+            assert!(parse(invalid).validate().is_err(), "{invalid}");
+            assert!(invalid.parse::<State>().is_err(), "{invalid}");
+        }
+        assert_eq!(State::new().side_to_move(), Crosses);
+        assert_eq!(parse("X../.../...").side_to_move(), Naughts);
+        assert_eq!(parse("X../.O./...").side_to_move(), Crosses);
+    }
+}
@@ -0,0 +1,156 @@
+use crate::*;
+use std::collections::HashSet;
+use std::convert::identity;
+
+/// The player to make a move at a ply, since the players alternate and crosses starts.
+pub(crate) fn turn_at(ply: usize) -> Player {
+    if ply.is_multiple_of(2) {
+        Crosses
+    } else {
+        Naughts
+    }
+}
+
+/// An iterator over the positions reachable from the initial position, one ply at a time.
+///
+/// Every item is the list of all positions after a number of moves, so the lengths of the items
+/// are the per-ply counts. Positions where the game is over are included but not expanded.
+#[derive(Debug, Clone)]
+pub struct ReachableLayers<G: Game> {
+    layer: Vec<G>,
+    ply: usize,
+    /// Maps every position to a representative, like `State::canonical`, to deduplicate
+    /// equivalent positions.
+    canonical: fn(G) -> G,
+}
+
+impl<G: Game> ReachableLayers<G> {
+    /// Enumerate all reachable positions.
+    pub fn new() -> Self {
+        Self::with_canonical(identity)
+    }
+
+    /// Enumerate the reachable positions where equivalent positions are only counted once.
+    pub fn with_canonical(canonical: fn(G) -> G) -> Self {
+        Self {
+            layer: vec![canonical(G::new())],
+            ply: 0,
+            canonical,
+        }
+    }
+}
+
+impl<G: Game> Default for ReachableLayers<G> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<G: Game> Iterator for ReachableLayers<G> {
+    type Item = Vec<G>;
+
+    fn next(&mut self) -> Option<Vec<G>> {
+        if self.layer.is_empty() {
+            return None;
+        }
+        let turn = turn_at(self.ply);
+        let mut seen = HashSet::new();
+        let mut next_layer = Vec::new();
+        for pos in &self.layer {
+            if pos.result(turn.opponent()).is_some() {
+                continue;
+            }
+            for m in pos.legal_moves() {
+                let mut child = *pos;
+                child.play(m, turn);
+                let child = (self.canonical)(child);
+                if seen.insert(child) {
+                    next_layer.push(child);
+                }
+            }
+        }
+        self.ply += 1;
+        Some(std::mem::replace(&mut self.layer, next_layer))
+    }
+}
+
+/// Iterate over all positions reachable from the initial position, ordered by ply.
+pub fn reachable_positions<G: Game>() -> impl Iterator<Item = G> {
+    ReachableLayers::new().flatten()
+}
+
+/// Iterate over all reachable positions where the game is not over, that is the positions where
+/// a move has to be selected.
+pub fn open_positions<G: Game>() -> impl Iterator<Item = G> {
+    ReachableLayers::<G>::new()
+        .enumerate()
+        .flat_map(|(ply, layer)| {
+            let last_player = turn_at(ply).opponent();
+            layer
+                .into_iter()
+                .filter(move |pos| pos.result(last_player).is_none())
+        })
+}
+
+/// Count the move sequences of exactly `depth` moves from a position where `turn` is the player
+/// to move. Sequences where the game ends before `depth` moves are not counted.
+pub fn perft<G: Game>(pos: G, turn: Player, depth: usize) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    pos.legal_moves()
+        .map(|m| {
+            let mut child = pos;
+            child.play(m, turn);
+            if child.result(turn).is_some() {
+                (depth == 1) as u64
+            } else {
+                perft(child, turn.opponent(), depth - 1)
+            }
+        })
+        .sum()
+}
+
+/// Count the number of distinct complete games from a position where `turn` is the player to move.
+pub fn count_games<G: Game>(pos: G, turn: Player) -> u64 {
+    pos.legal_moves()
+        .map(|m| {
+            let mut child = pos;
+            child.play(m, turn);
+            if child.result(turn).is_some() {
+                1
+            } else {
+                count_games(child, turn.opponent())
+            }
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reachable() {
+        let counts = ReachableLayers::<State>::new()
+            .map(|layer| layer.len())
+            .collect::<Vec<_>>();
+        assert_eq!(counts, [1, 9, 72, 252, 756, 1260, 1520, 1140, 390, 78]);
+        assert_eq!(counts.iter().sum::<usize>(), 5478);
+        let symmetric = ReachableLayers::with_canonical(|pos: State| pos.canonical())
+            .map(|layer| layer.len())
+            .collect::<Vec<_>>();
+        assert_eq!(symmetric, [1, 3, 12, 38, 108, 174, 204, 153, 57, 15]);
+        assert!(reachable_positions::<State>().all(|pos| pos.validate().is_ok()));
+        assert_eq!(open_positions::<State>().count(), 4520);
+    }
+
+    #[test]
+    fn test_perft() {
+        let expected = [1, 9, 72, 504, 3024, 15120, 54720, 148176, 200448, 127872];
+        for (depth, &count) in expected.iter().enumerate() {
+            assert_eq!(perft(State::new(), Crosses, depth), count);
+        }
+        assert_eq!(count_games(State::new(), Crosses), 255168);
+    }
+}
@@ -0,0 +1,400 @@
+use crate::*;
+use ratatui::crossterm::event::{
+    self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind, MouseButton,
+    MouseEventKind,
+};
+use ratatui::crossterm::execute;
+use ratatui::layout::{Alignment, Constraint, Layout, Position as CellPosition, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize as _};
+use ratatui::text::{Line, Text};
+use ratatui::widgets::{Block, Gauge, Paragraph, Sparkline};
+use ratatui::{DefaultTerminal, Frame};
+use std::io;
+use std::time::Duration;
+
+/// The number of training games between redraws of the dashboard.
+const GAMES_PER_FRAME: u32 = 10000;
+/// The number of training games summarized in every point of the history.
+const GAMES_PER_CHUNK: u32 = 100000;
+const CELL_WIDTH: u16 = 9;
+const CELL_HEIGHT: u16 = 5;
+
+/// The screen shown in the terminal UI.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Screen {
+    Training,
+    Playing,
+}
+
+/// A full-screen terminal UI for training the machine and playing against it.
+#[derive(Debug)]
+pub struct App<'a> {
+    machine: &'a mut Machine,
+    screen: Screen,
+    quit: bool,
+    /// The number of training games to play.
+    train_cycles: u32,
+    /// The number of training games played so far.
+    trained: u32,
+    /// Results since the last point in the history.
+    chunk: ResultStats,
+    /// The statistics for every chunk of `GAMES_PER_CHUNK` games.
+    history: Vec<ResultStats>,
+    /// The number of positions where the machine has to select a move.
+    open_positions: usize,
+    pos: Position,
+    machine_player: Player,
+    /// The selected cell.
+    cursor: Move,
+    /// The screen areas of the cells in the last drawn frame, for mouse clicks.
+    cell_areas: [Rect; Move::N],
+    message: String,
+}
+
+/// Run the terminal UI until the user quits. The machine is trained for `train_cycles` games
+/// while showing a dashboard.
+pub fn run(machine: &mut Machine, train_cycles: u32) -> io::Result<()> {
+    let mut terminal = ratatui::init();
+    let res = execute!(io::stdout(), EnableMouseCapture)
+        .and_then(|()| App::new(machine, train_cycles).run(&mut terminal));
+    // Restore the terminal before returning any error, so it isn't left in raw mode.
+    let disabled = execute!(io::stdout(), DisableMouseCapture);
+    ratatui::restore();
+    res.and(disabled)
+}
+
+impl<'a> App<'a> {
+    pub fn new(machine: &'a mut Machine, train_cycles: u32) -> Self {
+        Self {
+            machine,
+            screen: Screen::Training,
+            quit: false,
+            train_cycles,
+            trained: 0,
+            chunk: ResultStats::new(),
+            history: Vec::new(),
+            open_positions: open_positions::<State>().count(),
+            pos: Position::new(),
+            machine_player: Crosses,
+            cursor: Move::from_usize(Move::N / 2),
+            cell_areas: [Rect::default(); Move::N],
+            message: String::new(),
+        }
+    }
+
+    /// Draw frames and handle events until the user quits.
+    pub fn run(mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        while !self.quit {
+            terminal.draw(|frame| self.draw(frame))?;
+            let training = self.trained < self.train_cycles && self.screen == Screen::Training;
+            let timeout = if training {
+                Duration::ZERO
+            } else {
+                Duration::from_millis(250)
+            };
+            if event::poll(timeout)? {
+                self.handle_event(event::read()?);
+            }
+            if training {
+                self.train();
+            } else if self.screen == Screen::Playing {
+                self.machine_move();
+            }
+        }
+        Ok(())
+    }
+
+    /// Play a batch of training games.
+    fn train(&mut self) {
+        let games = GAMES_PER_FRAME.min(self.train_cycles - self.trained);
+        for _ in 0..games {
+            self.chunk.add(self.machine.play_training_match());
+            self.trained += 1;
+            if self.chunk.games() == GAMES_PER_CHUNK {
+                self.history.push(std::mem::take(&mut self.chunk));
+            }
+        }
+    }
+
+    /// Let the machine move if it is its turn.
+    fn machine_move(&mut self) {
+        if self.pos.result().is_some() || self.pos.turn() != self.machine_player {
+            return;
+        }
+        match self.machine.select_move(*self.pos.pos()) {
+            Some(m) => {
+                self.pos
+                    .play(m)
+                    .expect("The machine should only play legal moves.");
+                self.message = format!("The machine played {m}.");
+            }
+            None => self
+                .pos
+                .finish(Win {
+                    winner: self.machine_player.opponent(),
+                    reason: Resignation,
+                })
+                .unwrap(),
+        }
+    }
+
+    /// Make a move for the user.
+    fn play(&mut self, m: Move) {
+        if self.pos.turn() == self.machine_player {
+            return;
+        }
+        self.message = match self.pos.play(m) {
+            Ok(_) => String::new(),
+            Err(e) => e.to_string(),
+        };
+    }
+
+    /// Take back the machine's replies as well as the user's last move.
+    fn undo(&mut self) {
+        // The machine may have moved more than once since the user's last move after swapping
+        // sides.
+        if self.pos.turn() != self.machine_player
+            && self.pos.undo_by(self.machine_player.opponent()).is_ok()
+        {
+            self.message.clear();
+        }
+    }
+
+    fn handle_event(&mut self, event: Event) {
+        match event {
+            Event::Key(key) if key.kind == KeyEventKind::Press => match (self.screen, key.code) {
+                (_, KeyCode::Char('q') | KeyCode::Esc) => self.quit = true,
+                (Screen::Training, KeyCode::Char('p')) => self.screen = Screen::Playing,
+                (Screen::Playing, KeyCode::Char('t')) => self.screen = Screen::Training,
+                (Screen::Playing, KeyCode::Left) => self.move_cursor(0, -1),
+                (Screen::Playing, KeyCode::Right) => self.move_cursor(0, 1),
+                (Screen::Playing, KeyCode::Up) => self.move_cursor(-1, 0),
+                (Screen::Playing, KeyCode::Down) => self.move_cursor(1, 0),
+                (Screen::Playing, KeyCode::Enter | KeyCode::Char(' ')) => self.play(self.cursor),
+                (Screen::Playing, KeyCode::Char('u')) => self.undo(),
+                (Screen::Playing, KeyCode::Char('s')) => {
+                    self.machine_player = self.machine_player.opponent();
+                }
+                (Screen::Playing, KeyCode::Char('n')) => {
+                    self.pos = Position::new();
+                    self.message.clear();
+                }
+                _ => (),
+            },
+            Event::Mouse(mouse)
+                if self.screen == Screen::Playing
+                    && mouse.kind == MouseEventKind::Down(MouseButton::Left) =>
+            {
+                let clicked = CellPosition::new(mouse.column, mouse.row);
+                if let Some(i) = self.cell_areas.iter().position(|a| a.contains(clicked)) {
+                    self.cursor = Move::from_usize(i);
+                    self.play(self.cursor);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn move_cursor(&mut self, rows: isize, cols: isize) {
+        let i = self.cursor.to_usize() as isize;
+        let row = (i / 3 + rows).rem_euclid(3);
+        let col = (i % 3 + cols).rem_euclid(3);
+        self.cursor = Move::from_usize((row * 3 + col) as usize);
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        match self.screen {
+            Screen::Training => self.draw_training(frame),
+            Screen::Playing => self.draw_playing(frame),
+        }
+    }
+
+    fn draw_training(&self, frame: &mut Frame) {
+        let [progress_area, stats_area, history_area, help_area] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Length(6),
+            Constraint::Min(4),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let ratio = if self.train_cycles == 0 {
+            1.0
+        } else {
+            self.trained as f64 / self.train_cycles as f64
+        };
+        let title = format!("Training with seed {}", self.machine.seed());
+        let progress = Gauge::default()
+            .block(Block::bordered().title(title))
+            .gauge_style(Style::new().fg(Color::Green))
+            .label(format!("{} of {} games", self.trained, self.train_cycles))
+            .ratio(ratio);
+        frame.render_widget(progress, progress_area);
+
+        let latest = self.history.last().unwrap_or(&self.chunk);
+        let stats = Text::from(vec![
+            Line::from(format!(
+                "Positions learned: {} of {}",
+                self.machine.values().len(),
+                self.open_positions
+            )),
+            Line::from(format!("Draws: {:.1}%", latest.draws())),
+            Line::from(format!(
+                "Wins: crosses {:.1}%, naughts {:.1}%",
+                latest.wins(Crosses),
+                latest.wins(Naughts)
+            )),
+            Line::from(format!("Resignations: {:.1}%", latest.resignations())),
+        ]);
+        let title = format!("Outcomes of the last {} games", latest.games());
+        frame.render_widget(
+            Paragraph::new(stats).block(Block::bordered().title(title)),
+            stats_area,
+        );
+
+        let draws = self
+            .history
+            .iter()
+            .map(|chunk| chunk.draws().round() as u64)
+            .collect::<Vec<_>>();
+        let history = Sparkline::default()
+            .block(Block::bordered().title(format!("Draws per {GAMES_PER_CHUNK} games")))
+            .data(&draws)
+            .max(100)
+            .style(Style::new().fg(Color::Cyan));
+        frame.render_widget(history, history_area);
+
+        let help = if self.trained < self.train_cycles {
+            "p: play now  q: quit"
+        } else {
+            "Training finished.  p: play  q: quit"
+        };
+        frame.render_widget(Line::from(help).dim(), help_area);
+    }
+
+    fn draw_playing(&mut self, frame: &mut Frame) {
+        let [main_area, help_area] =
+            Layout::vertical([Constraint::Min(3 * CELL_HEIGHT), Constraint::Length(1)])
+                .areas(frame.area());
+        let [board_area, side_area] =
+            Layout::horizontal([Constraint::Length(3 * CELL_WIDTH + 2), Constraint::Min(20)])
+                .areas(main_area);
+        let board_block =
+            Block::bordered().title(format!("You play {}", self.machine_player.opponent()));
+        let board_inner = board_block.inner(board_area);
+        frame.render_widget(board_block, board_area);
+
+        let pos = *self.pos.pos();
+        // Looked up without inserting, so positions the machine never played are not counted
+        // as learned.
+        let scores = self.machine.values().get(&pos);
+        let winning_line = pos.winning_line();
+        let rows = Layout::vertical([Constraint::Length(CELL_HEIGHT); 3]).split(board_inner);
+        for (row, &row_area) in rows.iter().enumerate() {
+            let cols = Layout::horizontal([Constraint::Length(CELL_WIDTH); 3]).split(row_area);
+            for (col, &area) in cols.iter().enumerate() {
+                let m = Move::from_usize(row * 3 + col);
+                self.cell_areas[m.to_usize()] = area;
+                let mut border = Style::new();
+                if winning_line.is_some_and(|line| line.contains(&m)) {
+                    border = border.fg(Color::Green);
+                }
+                if self.pos.last_move() == Some(m) {
+                    border = border.add_modifier(Modifier::BOLD);
+                }
+                if m == self.cursor {
+                    border = border.fg(Color::Yellow);
+                }
+                let block = Block::bordered().title(m.to_string()).border_style(border);
+                let cell = match pos[m] {
+                    Cross => Paragraph::new("X").style(Style::new().fg(Color::Red).bold()),
+                    Naught => Paragraph::new("O").style(Style::new().fg(Color::Blue).bold()),
+                    Blank => match scores {
+                        // A heatmap of the machine's move probabilities.
+                        Some(scores) => {
+                            let p = scores.score(m);
+                            Paragraph::new(format!("{:.0}%", p * 100.0))
+                                .style(Style::new().bg(Color::Rgb(0, (40.0 + 160.0 * p) as u8, 0)))
+                        }
+                        None => Paragraph::new(""),
+                    },
+                };
+                let cell = cell.alignment(Alignment::Center).block(block);
+                frame.render_widget(cell, area);
+            }
+        }
+
+        let status = match self.pos.result() {
+            Some(Draw { reason }) => format!("Draw by {reason}."),
+            Some(Win { winner, reason }) if winner == self.machine_player => {
+                format!("The machine won by {reason}.")
+            }
+            Some(Win { reason, .. }) => format!("You won by {reason}."),
+            None if self.pos.turn() == self.machine_player => "Thinking...".to_string(),
+            None => "Your move.".to_string(),
+        };
+        let side = Text::from(vec![
+            Line::from(status),
+            Line::from(self.message.clone()),
+            Line::from(""),
+            Line::from(match scores {
+                Some(scores) => format!("Move scores: {scores}"),
+                None => "The machine has not learned this position.".to_string(),
+            }),
+        ]);
+        frame.render_widget(
+            Paragraph::new(side)
+                .wrap(ratatui::widgets::Wrap { trim: true })
+                .block(Block::bordered().title("Game")),
+            side_area,
+        );
+        frame.render_widget(
+            Line::from(
+                "arrows/mouse: select  enter: play  u: undo  s: swap  n: new  t: training  q: quit",
+            )
+            .dim(),
+            help_area,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    #[test]
+    fn test_draw() {
+        let mut machine = Machine::new();
+        let mut app = App::new(&mut machine, 100);
+        let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+        app.train();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+        let contains = |terminal: &Terminal<TestBackend>, s: &str| {
+            let buffer = terminal.backend().buffer();
+            (0..buffer.area.height).any(|y| {
+                (0..buffer.area.width)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect::<String>()
+                    .contains(s)
+            })
+        };
+        assert!(contains(&terminal, "100 of 100 games"));
+
+        app.screen = Screen::Playing;
+        app.machine_move();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+        assert!(contains(&terminal, "You play Naughts"));
+        assert_eq!(app.pos.history().len(), 1);
+        // Click on the centre of a free cell.
+        let free = app.pos.pos().legal_moves().iter().next().unwrap();
+        let area = app.cell_areas[free.to_usize()];
+        app.handle_event(Event::Mouse(event::MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column: area.x + area.width / 2,
+            row: area.y + area.height / 2,
+            modifiers: event::KeyModifiers::NONE,
+        }));
+        assert_eq!(app.pos.last_move(), Some(free));
+    }
+}
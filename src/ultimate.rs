@@ -0,0 +1,212 @@
+use crate::*;
+use anyhow::Context as _;
+use std::fmt;
+use std::str::FromStr;
+
+/// A move in ultimate tic-tac-toe: a cell on one of the nine local boards.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub struct UltimateMove {
+    /// The local board to play on.
+    pub board: Move,
+    /// The cell on the local board.
+    pub cell: Move,
+}
+
+/// A state in ultimate tic-tac-toe.
+///
+/// The game is played on nine local tic-tac-toe boards arranged in a 3×3 grid. The cell of a move
+/// dictates the local board the opponent must play on next. If that board is already won or full,
+/// the opponent may play on any open board. Winning three local boards in a row wins the game.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct UltimateState {
+    /// boards[b] = the local board b:
+    boards: [State; Move::N],
+    /// The global board where every won local board is marked by its winner.
+    outcomes: State,
+    /// The local board the next move must be played on, or `None` if any open board is allowed.
+    next_board: Option<Move>,
+}
+
+impl GameMove for UltimateMove {
+    const N: usize = Move::N * Move::N;
+
+    fn to_usize(self) -> usize {
+        self.board.to_usize() * Move::N + self.cell.to_usize()
+    }
+
+    fn from_usize(i: usize) -> Self {
+        Self {
+            board: Move::from_usize(i / Move::N),
+            cell: Move::from_usize(i % Move::N),
+        }
+    }
+}
+
+impl fmt::Display for UltimateMove {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.board, self.cell)
+    }
+}
+
+impl FromStr for UltimateMove {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let split = s
+            .char_indices()
+            .skip(1)
+            .find(|(_, c)| c.is_alphabetic())
+            .map(|(i, _)| i)
+            .context("An ultimate move must be a board followed by a cell, like b2a3.")?;
+        let (board, cell) = s.split_at(split);
+        Ok(Self {
+            board: board.parse().context("Bad board in ultimate move.")?,
+            cell: cell.parse().context("Bad cell in ultimate move.")?,
+        })
+    }
+}
+
+impl Default for UltimateState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UltimateState {
+    /// Nine empty boards.
+    pub fn new() -> Self {
+        Self {
+            boards: [State::new(); Move::N],
+            outcomes: State::new(),
+            next_board: None,
+        }
+    }
+
+    /// Get a local board.
+    pub fn board(&self, b: Move) -> &State {
+        &self.boards[b.to_usize()]
+    }
+
+    /// The global board where every won local board is marked by its winner.
+    pub fn outcomes(&self) -> &State {
+        &self.outcomes
+    }
+
+    /// The local board the next move must be played on, or `None` if any open board is allowed.
+    pub fn next_board(&self) -> Option<Move> {
+        self.next_board
+    }
+
+    /// Check if a local board is won or full.
+    pub fn is_closed(&self, b: Move) -> bool {
+        self.outcomes[b] != Blank || self.board(b).is_draw()
+    }
+
+    /// All legal moves in the position.  Garanteed to be ordered by `GameMove::to_usize()`.
+    pub fn legal_moves(&self) -> impl Iterator<Item = UltimateMove> + '_ {
+        Move::all()
+            .filter(|&b| !self.is_closed(b) && self.next_board.is_none_or(|next| next == b))
+            .flat_map(|board| {
+                self.board(board)
+                    .legal_moves()
+                    .iter()
+                    .map(move |cell| UltimateMove { board, cell })
+            })
+    }
+
+    /// Make a move with the given mark.
+    pub fn play(&mut self, m: UltimateMove, player: Player) {
+        debug_assert!(!self.is_closed(m.board));
+        debug_assert!(self.next_board.is_none_or(|next| next == m.board));
+        let board = &mut self.boards[m.board.to_usize()];
+        board.play(m.cell, player);
+        if let Some(Win { .. }) = board.result(player) {
+            self.outcomes.play(m.board, player);
+        }
+        self.next_board = (!self.is_closed(m.cell)).then_some(m.cell);
+    }
+
+    /// Given the player who made the last move, return the result if the game is over.
+    pub fn result(&self, player: Player) -> Option<GameResult> {
+        match self.outcomes.result(player) {
+            Some(res @ Win { .. }) => Some(res),
+            _ if Move::all().all(|b| self.is_closed(b)) => Some(Draw { reason: BoardFull }),
+            _ => None,
+        }
+    }
+}
+
+impl Game for UltimateState {
+    type Move = UltimateMove;
+
+    fn new() -> Self {
+        UltimateState::new()
+    }
+
+    fn ply(&self) -> usize {
+        self.boards.iter().map(Game::ply).sum()
+    }
+
+    fn legal_moves(&self) -> impl Iterator<Item = UltimateMove> {
+        UltimateState::legal_moves(self)
+    }
+
+    fn play(&mut self, m: UltimateMove, player: Player) {
+        UltimateState::play(self, m, player)
+    }
+
+    fn result(&self, player: Player) -> Option<GameResult> {
+        UltimateState::result(self, player)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mv(s: &str) -> UltimateMove {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_move_notation() {
+        for i in 0..UltimateMove::N {
+            let m = UltimateMove::from_usize(i);
+            assert_eq!(m.to_usize(), i);
+            assert_eq!(mv(&m.to_string()), m);
+        }
+        assert!("b2".parse::<UltimateMove>().is_err());
+        assert!("b2d1".parse::<UltimateMove>().is_err());
+    }
+
+    #[test]
+    fn test_next_board() {
+        let mut pos = UltimateState::new();
+        assert_eq!(pos.legal_moves().count(), UltimateMove::N);
+        pos.play(mv("b2a3"), Crosses);
+        assert_eq!(pos.next_board(), Some(mv("a3a1").board));
+        assert!(pos.legal_moves().all(|m| m.board == mv("a3a1").board));
+        assert_eq!(pos.legal_moves().count(), Move::N);
+
+        // Win the local board a1 for crosses and send naughts there.
+        for (m, player) in [
+            ("a3a1", Naughts),
+            ("a1a2", Crosses),
+            ("a2a1", Naughts),
+            ("a1b2", Crosses),
+            ("b2a1", Naughts),
+            ("a1c2", Crosses),
+        ] {
+            assert_eq!(pos.result(player.opponent()), None);
+            pos.play(mv(m), player);
+        }
+        assert_eq!(pos.outcomes()[mv("a1a1").board], Cross);
+        assert!(pos.is_closed(mv("a1a1").board));
+        assert_eq!(pos.result(Crosses), None);
+        // c2 is still open, so naughts must play there:
+        assert!(pos.legal_moves().all(|m| m.board == mv("c2a1").board));
+        pos.play(mv("c2a1"), Naughts);
+        // a1 is closed, so crosses may play anywhere else:
+        assert_eq!(pos.next_board(), None);
+        assert!(pos.legal_moves().all(|m| m.board != mv("a1a1").board));
+    }
+}
@@ -0,0 +1,167 @@
+use crate::*;
+use anyhow::Context as _;
+use std::fmt;
+use std::str::FromStr;
+
+/// A move in wild tic-tac-toe: a cell and the mark to place there.
+///
+/// Written as the mark followed by the cell, like "Ob2".
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct WildMove {
+    pub cell: Move,
+    /// Either `Cross` or `Naught`.
+    pub mark: Mark,
+}
+
+/// A state in wild tic-tac-toe, where both players may place either mark and whoever completes
+/// a line of three equal marks wins.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub struct WildState(State);
+
+impl WildMove {
+    pub const N: usize = 2 * Move::N;
+}
+
+impl GameMove for WildMove {
+    const N: usize = WildMove::N;
+
+    fn to_usize(self) -> usize {
+        debug_assert_ne!(self.mark, Blank);
+        self.mark as usize * Move::N + self.cell.to_usize()
+    }
+
+    fn from_usize(i: usize) -> Self {
+        Self {
+            cell: Move::from_usize(i % Move::N),
+            mark: if i < Move::N { Cross } else { Naught },
+        }
+    }
+}
+
+impl fmt::Display for WildMove {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.mark.to_char(), self.cell)
+    }
+}
+
+impl FromStr for WildMove {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let mut chars = s.chars();
+        let mark = match chars.next().context("A move cannot be an empty string.")? {
+            'X' | 'x' => Cross,
+            'O' | 'o' => Naught,
+            _ => anyhow::bail!("A wild move must start with the mark X or O, like Ob2."),
+        };
+        Ok(Self {
+            cell: chars.as_str().parse()?,
+            mark,
+        })
+    }
+}
+
+impl WildState {
+    /// An empty board.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The board with the marks.
+    pub fn board(&self) -> &State {
+        &self.0
+    }
+
+    /// All legal moves in the position.  Garanteed to be ordered by `GameMove::to_usize()`.
+    pub fn legal_moves(&self) -> impl Iterator<Item = WildMove> + '_ {
+        [Cross, Naught].into_iter().flat_map(|mark| {
+            self.0
+                .legal_moves()
+                .iter()
+                .map(move |cell| WildMove { cell, mark })
+        })
+    }
+
+    /// Place a mark. The mark does not depend on the player.
+    pub fn play(&mut self, m: WildMove) {
+        debug_assert_eq!(self.0[m.cell], Blank);
+        debug_assert_ne!(m.mark, Blank);
+        self.0[m.cell] = m.mark;
+    }
+
+    /// Given the player who made the last move, return the result if the game is over.
+    ///
+    /// The last player wins if there is a line of any mark.
+    pub fn result(&self, player: Player) -> Option<GameResult> {
+        let marks = [Crosses, Naughts];
+        if marks
+            .iter()
+            .any(|&mark| self.0.has_row(mark) || self.0.has_column(mark))
+        {
+            Some(Win {
+                winner: player,
+                reason: RowOrColumn,
+            })
+        } else if marks.iter().any(|&mark| self.0.has_diagonal(mark)) {
+            Some(Win {
+                winner: player,
+                reason: Diagonal,
+            })
+        } else if self.0.is_draw() {
+            Some(Draw { reason: BoardFull })
+        } else {
+            None
+        }
+    }
+}
+
+impl Game for WildState {
+    type Move = WildMove;
+
+    fn new() -> Self {
+        WildState::new()
+    }
+
+    fn ply(&self) -> usize {
+        self.0.ply()
+    }
+
+    fn legal_moves(&self) -> impl Iterator<Item = WildMove> {
+        WildState::legal_moves(self)
+    }
+
+    fn play(&mut self, m: WildMove, _player: Player) {
+        WildState::play(self, m)
+    }
+
+    fn result(&self, player: Player) -> Option<GameResult> {
+        WildState::result(self, player)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wild() {
+        let mut pos = WildState::new();
+        assert_eq!(pos.legal_moves().count(), WildMove::N);
+        for (i, m) in pos.legal_moves().enumerate() {
+            assert_eq!(m.to_usize(), i);
+            assert_eq!(m.to_string().parse::<WildMove>().unwrap(), m);
+        }
+        // Crosses completes a line of naughts and wins.
+        for (m, player) in [("Oa1", Crosses), ("Ob2", Naughts)] {
+            pos.play(m.parse().unwrap());
+            assert_eq!(pos.result(player), None);
+        }
+        pos.play("Oc3".parse().unwrap());
+        assert_eq!(
+            pos.result(Crosses),
+            Some(Win {
+                winner: Crosses,
+                reason: Diagonal
+            })
+        );
+    }
+}